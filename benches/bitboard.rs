@@ -0,0 +1,70 @@
+#![feature(test)]
+#![allow(non_snake_case)]
+
+extern crate test;
+
+use judge::bitboard::BitBoard;
+use judge::board::{Board, GameBoard, GameBoardShotResult};
+use judge::position::Position;
+use judge::random::Random;
+use judge::rules::GameRules;
+
+/// A board with the classic fleet: the ships are placed in the top left corner row by row from
+/// the largest one.
+const BOARD: &str = "\
+                     ####______\n__________\n###_###___\n__________\n##_##_##__\n\
+                     __________\n#_#_#_#___\n__________\n__________\n__________";
+
+/// The cells of the board in a shuffled order.
+fn shots(seed: u64) -> Vec<Position> {
+    let mut random = Random::new(seed);
+    let mut positions: Vec<Position> = Position::top_left().iter().collect();
+    for index in (1..positions.len()).rev() {
+        positions.swap(index, random.below(index + 1));
+    }
+    positions
+}
+
+/// Shoots at the cells of the board in the order, revealing the cells around the sunk ships.
+fn play<B: Board>(board: &mut B, shots: &[Position]) -> usize {
+    let mut sunk_ships = 0;
+    for &position in shots {
+        if board.shoot(position) == GameBoardShotResult::Sunk {
+            board.reveal_around_ship(position);
+            sunk_ships += 1;
+        }
+    }
+    sunk_ships
+}
+
+fn bench_validation<B: Board>(bencher: &mut test::Bencher) {
+    let rules = GameRules::default();
+    bencher.iter(|| B::from_lines_with_rules(test::black_box(BOARD).split('\n'), &rules));
+}
+
+/// Shoots at every cell of the board, as in a game.
+fn bench_game<B: Board>(bencher: &mut test::Bencher) {
+    let board = B::from_lines_with_rules(BOARD.split('\n'), &GameRules::default()).unwrap();
+    let shots = shots(0);
+    bencher.iter(|| play(&mut board.clone(), test::black_box(&shots)));
+}
+
+#[bench]
+fn bench_GameBoard_validation(bencher: &mut test::Bencher) {
+    bench_validation::<GameBoard>(bencher);
+}
+
+#[bench]
+fn bench_BitBoard_validation(bencher: &mut test::Bencher) {
+    bench_validation::<BitBoard>(bencher);
+}
+
+#[bench]
+fn bench_GameBoard_game(bencher: &mut test::Bencher) {
+    bench_game::<GameBoard>(bencher);
+}
+
+#[bench]
+fn bench_BitBoard_game(bencher: &mut test::Bencher) {
+    bench_game::<BitBoard>(bencher);
+}
@@ -0,0 +1,178 @@
+use crate::game_log::GameLog;
+use crate::raster::{encode_gif, Frame, Raster};
+use crate::rules::GameRules;
+use crate::snapshot::{self, BoardView, Canvas, Color, BOARD_IMAGE_SIZE, PALETTE};
+
+/// Height of the players' names above the boards.
+const HEADER_HEIGHT: i32 = 32;
+/// Height of the caption (the last shot or the verdict) under the boards.
+const FOOTER_HEIGHT: i32 = 40;
+const BOARDS_GAP: i32 = 16;
+const FRAME_WIDTH: i32 = BOARD_IMAGE_SIZE * 2 + BOARDS_GAP;
+const FRAME_HEIGHT: i32 = HEADER_HEIGHT + BOARD_IMAGE_SIZE + FOOTER_HEIGHT;
+
+/// Render a finished game from its log into an animated GIF with a frame per shot
+#[derive(Debug, structopt::StructOpt)]
+pub struct RenderGifArgs {
+    #[structopt(parse(from_os_str))]
+    log: std::path::PathBuf,
+    /// Where to write the animation (the log path with the `.gif` extension by default)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<std::path::PathBuf>,
+    /// How long every shot is shown, in milliseconds
+    #[structopt(long = "delay", default_value = "500")]
+    delay: u32,
+    /// How long the final frame with the verdict is shown, in milliseconds
+    #[structopt(long = "hold", default_value = "3000")]
+    hold: u32,
+    /// `full` (the whole boards) or `fog` (only what the players know about each other's boards)
+    #[structopt(long = "view", default_value = "full")]
+    view: BoardView,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub fn render_gif(args: RenderGifArgs) -> Result<(), String> {
+    let log = std::fs::read_to_string(&args.log)
+        .map_err(|err| format!("failed to read {}: {}", args.log.display(), err))?;
+    let game_log = GameLog::parse(&log, &args.rules)
+        .map_err(|err| format!("failed to parse {}: {}", args.log.display(), err))?;
+    if game_log.boards.is_none() {
+        return Err("the log has no boards".to_owned());
+    }
+    let output = match args.output {
+        Some(output) => output,
+        None => args.log.with_extension("gif"),
+    };
+    let frames = frames(&game_log, args.view, args.delay, args.hold);
+    std::fs::write(&output, encode_gif(&frames, &PALETTE))
+        .map_err(|err| format!("failed to write {}: {}", output.display(), err))?;
+    println!(
+        "The animation of {} frames is saved to {}",
+        frames.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Replays the game and draws both boards side by side before the first shot and after every
+/// shot (`delay` milliseconds each), and then holds the final boards with the verdict for `hold`
+/// milliseconds.
+pub fn frames(game_log: &GameLog, view: BoardView, delay: u32, hold: u32) -> Vec<Frame> {
+    let states = game_log.replay();
+    let mut frames = Vec::with_capacity(states.len() + 1);
+    for (step, boards) in states.iter().enumerate() {
+        let shot = step.checked_sub(1).map(|index| &game_log.shots[index]);
+        let caption = match shot {
+            Some(shot) => format!("{}. {}", step, shot),
+            None => "Start".to_owned(),
+        };
+        let mut raster = Raster::new(
+            FRAME_WIDTH as u16,
+            FRAME_HEIGHT as u16,
+            Color::Background.index(),
+        );
+        for (index, board) in boards.iter().enumerate() {
+            let left = index as i32 * (BOARD_IMAGE_SIZE + BOARDS_GAP);
+            let title = format!("Player {}", index + 1);
+            let title_center = (left + BOARD_IMAGE_SIZE / 2, HEADER_HEIGHT / 2 + 4);
+            Canvas::text(&mut raster, title_center, &title, 15, Color::Label);
+            let origin = (left, HEADER_HEIGHT);
+            snapshot::draw_board(&mut raster, origin, &snapshot::glyphs(board, view));
+            if let Some(shot) = shot {
+                if usize::from(shot.player_number) != index + 1 {
+                    snapshot::highlight_cell(&mut raster, origin, shot.position);
+                }
+            }
+        }
+        let mut final_raster = None;
+        if step + 1 == states.len() {
+            final_raster = Some(raster.clone());
+        }
+        draw_caption(&mut raster, &caption);
+        frames.push(Frame {
+            raster,
+            delay: to_centiseconds(delay),
+        });
+
+        if let Some(mut raster) = final_raster {
+            let verdict = match game_log.result {
+                Some(game_result) => game_result.as_str(),
+                None => "The game is not finished",
+            };
+            draw_caption(&mut raster, verdict);
+            frames.push(Frame {
+                raster,
+                delay: to_centiseconds(hold),
+            });
+        }
+    }
+    frames
+}
+
+/// Writes the caption under the boards with the largest font it fits with.
+fn draw_caption(raster: &mut Raster, caption: &str) {
+    let center = (FRAME_WIDTH / 2, FRAME_HEIGHT - FOOTER_HEIGHT / 2);
+    let scale = (1..=3)
+        .rev()
+        .find(|&scale| Raster::text_width(caption, scale) <= FRAME_WIDTH - BOARDS_GAP)
+        .unwrap_or(1);
+    Canvas::text(raster, center, caption, scale * 5, Color::Label);
+}
+
+fn to_centiseconds(milliseconds: u32) -> u16 {
+    std::cmp::min(milliseconds / 10, 0xFFFF) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures;
+    use crate::position::Position;
+    use crate::snapshot::CELL_SIZE;
+
+    #[test]
+    fn test_frames() {
+        let log = fixtures::log_boards()
+            + "1 1 1 sunk\n1 2 1 miss\n2 1 1 hit\n\
+                   Player 2 won the game!\n";
+        let game_log = GameLog::parse(&log, &GameRules::default()).unwrap();
+        let frames = frames(&game_log, BoardView::Full, 250, 4000);
+        let delays: Vec<u16> = frames.iter().map(|frame| frame.delay).collect();
+        assert_eq!(delays, [25, 25, 25, 25, 400]);
+
+        // The cell at `x y` of the first (0) or the second (1) board is `dx` pixels to the right
+        // from its top left corner
+        let pixel = |frame: &Frame, board: i32, position: &str, dx: i32| {
+            let position: Position = position.parse().unwrap();
+            let x = board * (BOARD_IMAGE_SIZE + BOARDS_GAP)
+                + CELL_SIZE * (i32::from(position.x()) + 1)
+                + dx;
+            let y = HEADER_HEIGHT + CELL_SIZE * (i32::from(position.y()) + 1) + CELL_SIZE / 2;
+            frame.raster.get(x as u16, y as u16)
+        };
+        assert_eq!(pixel(&frames[0], 1, "1 1", 5), Color::Ship.index());
+        assert_eq!(pixel(&frames[1], 1, "1 1", 5), Color::Sunk.index());
+        assert_eq!(pixel(&frames[1], 1, "1 1", 1), Color::Highlight.index());
+        assert_eq!(pixel(&frames[2], 1, "1 1", 1), Color::Water.index());
+        assert_eq!(pixel(&frames[3], 0, "1 1", 1), Color::Highlight.index());
+        assert_eq!(pixel(&frames[3], 0, "1 1", 5), Color::Ship.index());
+        // Only the caption differs in the final frame
+        let footer = |frame: &Frame| {
+            (0..FRAME_WIDTH as u16)
+                .map(|x| {
+                    frame
+                        .raster
+                        .get(x, (FRAME_HEIGHT - FOOTER_HEIGHT / 2) as u16)
+                })
+                .collect::<Vec<u8>>()
+        };
+        assert_ne!(footer(&frames[3]), footer(&frames[4]));
+        assert_eq!(
+            pixel(&frames[3], 0, "1 1", 5),
+            pixel(&frames[4], 0, "1 1", 5)
+        );
+    }
+}
@@ -0,0 +1,357 @@
+use crate::board::{Board, GameBoardShotResult};
+use crate::cells::{GameBoardCell, GameBoardCellState};
+use crate::layout::Layout;
+use crate::position::{Position, Symmetry};
+use crate::random::Random;
+use crate::rules::GameRules;
+use crate::{InvalidInputError, GAME_BOARD_SIZE};
+
+/// A set of cells of the board as bits indexed by `Position::yx`.
+pub type Mask = u128;
+
+const CELLS_COUNT: u8 = GAME_BOARD_SIZE * GAME_BOARD_SIZE;
+pub const ALL_CELLS: Mask = (1 << CELLS_COUNT) - 1;
+const LEFT_COLUMN: Mask = 0x401_0040_1004_0100_4010_0401;
+const RIGHT_COLUMN: Mask = LEFT_COLUMN << (GAME_BOARD_SIZE - 1);
+
+pub fn bit(position: Position) -> Mask {
+    1 << position.yx()
+}
+
+/// The positions of the cells in the mask from the top-left one.
+pub fn positions(mut mask: Mask) -> impl Iterator<Item = Position> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            return None;
+        }
+        let index = mask.trailing_zeros() as u8;
+        mask &= mask - 1;
+        Position::from_xy(index % GAME_BOARD_SIZE, index / GAME_BOARD_SIZE)
+    })
+}
+
+/// The cells of the mask rotated or mirrored by the `symmetry`.
+pub fn transform(mask: Mask, symmetry: Symmetry) -> Mask {
+    positions(mask).fold(0, |transformed, position| {
+        transformed | bit(position.transform(symmetry))
+    })
+}
+
+/// The cells of the mask together with the cells to the left, right, above and below them.
+pub fn dilate_orthogonally(mask: Mask) -> Mask {
+    mask | (mask & !LEFT_COLUMN) >> 1
+        | (mask & !RIGHT_COLUMN) << 1
+        | mask >> GAME_BOARD_SIZE
+        | (mask << GAME_BOARD_SIZE) & ALL_CELLS
+}
+
+/// The cells of the mask together with all their neighbours including the diagonal ones.
+pub fn dilate(mask: Mask) -> Mask {
+    let row = mask | (mask & !LEFT_COLUMN) >> 1 | (mask & !RIGHT_COLUMN) << 1;
+    row | row >> GAME_BOARD_SIZE | (row << GAME_BOARD_SIZE) & ALL_CELLS
+}
+
+/// The lowest cell of the mask (the top-left one).
+pub fn lowest(mask: Mask) -> Mask {
+    mask & mask.wrapping_neg()
+}
+
+/// The cells of the `area` connected to the `seed` cells.
+pub fn component(seed: Mask, area: Mask) -> Mask {
+    let mut component = seed & area;
+    loop {
+        let next = dilate_orthogonally(component) & area;
+        if next == component {
+            return component;
+        }
+        component = next;
+    }
+}
+
+/// The board kept as bit masks of the cells, so that shooting and checking whether a ship is
+/// sunk take a few bit operations. It only implements the `Board` trait (parsing, shooting and
+/// revealing cells) rather than the whole API of `GameBoard`, and is meant for simulating a lot
+/// of games; the boards of the judge, the interactive mode and the log tools are `GameBoard`s.
+#[derive(Debug, Clone)]
+pub struct BitBoard {
+    ships: Mask,
+    mines: Mask,
+    shots: Mask,
+    /// Ship cells revealed to the opponent as a penalty for hitting mines.
+    revealed: Mask,
+    /// The cells of every ship ordered by their top-left cell.
+    ship_masks: Vec<Mask>,
+    /// Characters the ships in `ship_masks` were marked with (`#` or a letter).
+    labels: Vec<char>,
+    hits_left: u8,
+    ships_left: u8,
+}
+
+impl BitBoard {
+    fn empty() -> Self {
+        Self {
+            ships: 0,
+            mines: 0,
+            shots: 0,
+            revealed: 0,
+            ship_masks: Vec::new(),
+            labels: Vec::new(),
+            hits_left: 0,
+            ships_left: 0,
+        }
+    }
+
+    /// Parses and validates the board the same way `GameBoard::from_lines_with_rules` does.
+    pub fn from_lines_with_rules<Item, I>(
+        lines: I,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
+    where
+        Item: AsRef<str>,
+        I: Iterator<Item = Item>,
+    {
+        let layout = Layout::from_lines_with_rules(lines, rules)?;
+        let mut board = Self::empty();
+        board.mines = layout.mines;
+        for (label, ship) in layout.ships {
+            board.ships |= ship;
+            board.hits_left += ship.count_ones() as u8;
+            board.ship_masks.push(ship);
+            board.labels.push(label);
+        }
+        board.ships_left = board.ship_masks.len() as u8;
+        Ok(board)
+    }
+
+    pub fn get(&self, position: Position) -> GameBoardCell {
+        let cell = bit(position);
+        let state = if self.shots & cell != 0 {
+            GameBoardCellState::Shot
+        } else {
+            GameBoardCellState::NonShot
+        };
+        if self.ships & cell != 0 {
+            GameBoardCell::Ship(state)
+        } else if self.mines & cell != 0 {
+            GameBoardCell::Mine(state)
+        } else {
+            GameBoardCell::Empty(state)
+        }
+    }
+
+    /// The index of the ship occupying any of the cells.
+    fn ship_index(&self, cells: Mask) -> Option<usize> {
+        self.ship_masks.iter().position(|&ship| ship & cells != 0)
+    }
+
+    pub fn shoot(&mut self, position: Position) -> GameBoardShotResult {
+        let cell = bit(position);
+        if self.shots & cell != 0 {
+            return GameBoardShotResult::Miss;
+        }
+        self.shots |= cell;
+        if self.ships & cell != 0 {
+            self.hits_left -= 1;
+            let ship = self.ship_masks[self
+                .ship_index(cell)
+                .expect("every ship cell belongs to a ship")];
+            if ship & !self.shots == 0 {
+                self.ships_left -= 1;
+                GameBoardShotResult::Sunk
+            } else {
+                GameBoardShotResult::Hit
+            }
+        } else if self.mines & cell != 0 {
+            GameBoardShotResult::Mine
+        } else {
+            GameBoardShotResult::Miss
+        }
+    }
+
+    /// Marks all the cells around the ship at the given position as shot since no other ship can
+    /// be there. Returns positions of the ship cells.
+    pub fn reveal_around_ship(&mut self, position: Position) -> Vec<Position> {
+        let ship = match self.ship_index(bit(position)) {
+            Some(index) => self.ship_masks[index],
+            None => return Vec::new(),
+        };
+        self.shots |= dilate(ship) & !self.ships & !self.mines;
+        positions(ship).collect()
+    }
+
+    /// Picks a random ship cell that has been neither shot nor revealed yet to reveal it to the
+    /// opponent, the same one `GameBoard::reveal_ship_cell` picks.
+    pub fn reveal_ship_cell(&mut self, random: &mut Random) -> Option<Position> {
+        let cells = self.ships & !self.shots & !self.revealed;
+        if cells == 0 {
+            return None;
+        }
+        let position = positions(cells).nth(random.below(cells.count_ones() as usize))?;
+        self.revealed |= bit(position);
+        Some(position)
+    }
+
+    pub fn hits_left(&self) -> u8 {
+        self.hits_left
+    }
+
+    pub fn ships_left(&self) -> u8 {
+        self.ships_left
+    }
+}
+
+/// Renders the board the same way as `GameBoard::to_string`.
+impl std::fmt::Display for BitBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for position in Position::top_left().iter() {
+            let cell = self.get(position);
+            let label = match (cell, self.ship_index(bit(position))) {
+                (GameBoardCell::Ship(GameBoardCellState::NonShot), Some(index)) => {
+                    self.labels[index]
+                }
+                (cell, _) => cell.into(),
+            };
+            if position.x() == GAME_BOARD_SIZE - 1 {
+                writeln!(f, "{}", label)?;
+            } else {
+                write!(f, "{}", label)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Board for BitBoard {
+    fn from_lines_with_rules<Item, I>(
+        lines: I,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
+    where
+        Item: AsRef<str>,
+        I: Iterator<Item = Item>,
+    {
+        BitBoard::from_lines_with_rules(lines, rules)
+    }
+
+    fn get(&self, position: Position) -> GameBoardCell {
+        BitBoard::get(self, position)
+    }
+
+    fn shoot(&mut self, position: Position) -> GameBoardShotResult {
+        BitBoard::shoot(self, position)
+    }
+
+    fn reveal_around_ship(&mut self, position: Position) -> Vec<Position> {
+        BitBoard::reveal_around_ship(self, position)
+    }
+
+    fn reveal_ship_cell(&mut self, random: &mut Random) -> Option<Position> {
+        BitBoard::reveal_ship_cell(self, random)
+    }
+
+    fn hits_left(&self) -> u8 {
+        BitBoard::hits_left(self)
+    }
+
+    fn ships_left(&self) -> u8 {
+        BitBoard::ships_left(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::board::GameBoard;
+    use crate::fixtures::{rules, BOARD};
+
+    /// The cells of the board in a shuffled order.
+    fn shots(seed: u64) -> Vec<Position> {
+        let mut random = Random::new(seed);
+        let mut positions: Vec<Position> = Position::top_left().iter().collect();
+        for index in (1..positions.len()).rev() {
+            positions.swap(index, random.below(index + 1));
+        }
+        positions
+    }
+
+    #[test]
+    fn test_BitBoard_validation_matches_GameBoard() {
+        let corner_contact = BOARD.replace(
+            "##_##_##__\n__________\n#_#_#_#___",
+            "##_##_##__\n________#_\n__#_#_#___",
+        );
+        let side_contact = BOARD.replace("####", "AAAA").replace(
+            "##_##_##__\n__________\n#_#_#_#___",
+            "##_##_BBC_\n__________\n__#_#_#___",
+        );
+        let cases: &[(&str, &[&str])] = &[
+            (BOARD, &[]),
+            (&BOARD.replace("#_#_#_#___", "#_#_#__#__"), &[]),
+            (&BOARD.replace("#_#_#_#___", "#_#_#_##__"), &[]),
+            (
+                &BOARD.replace("#_#_#_#___", "#_#_#_#*__"),
+                &["--mines", "1"],
+            ),
+            (&BOARD.replace("#_#_#_#___", "#_#_#_#*__"), &[]),
+            (&corner_contact, &[]),
+            (&corner_contact, &["--placement", "corners"]),
+            (&side_contact, &["--placement", "corners"]),
+            (&side_contact, &["--placement", "any"]),
+            (&BOARD.replace("###_###", "BBB_CCC"), &[]),
+            (&BOARD.replace("####", "XXXX"), &[]),
+            (
+                &BOARD.replace("####", "AAAA").replace("#_#_#_#", "A_#_#_#"),
+                &[],
+            ),
+            (&BOARD.replace("####", "#%##"), &[]),
+            (&BOARD.replace("####______", "####_______"), &[]),
+            (&BOARD.replace("\n__________", ""), &[]),
+        ];
+        for &(lines, args) in cases {
+            let rules = rules(args);
+            let board = GameBoard::from_lines_with_rules(lines.split('\n'), &rules);
+            let bit_board = BitBoard::from_lines_with_rules(lines.split('\n'), &rules);
+            assert_eq!(bit_board.is_ok(), board.is_ok(), "{}\n{:?}", lines, args);
+            if let (Ok(board), Ok(bit_board)) = (board, bit_board) {
+                assert_eq!(bit_board.to_string(), board.to_string());
+                assert_eq!(bit_board.hits_left(), board.hits_left());
+                assert_eq!(bit_board.ships_left(), board.ships_left());
+            }
+        }
+    }
+
+    #[test]
+    fn test_BitBoard_shoot_matches_GameBoard() {
+        let lines = BOARD
+            .replace("####", "AAAA")
+            .replace("#_#_#_#___", "#_#_#_#*__");
+        let rules = rules(&["--mines", "1"]);
+        for seed in 0..20 {
+            let mut board = GameBoard::from_lines_with_rules(lines.split('\n'), &rules).unwrap();
+            let mut bit_board = BitBoard::from_lines_with_rules(lines.split('\n'), &rules).unwrap();
+            let (mut random, mut bit_random) = (Random::new(seed), Random::new(seed));
+            assert_eq!(
+                bit_board.reveal_ship_cell(&mut bit_random),
+                board.reveal_ship_cell(&mut random)
+            );
+            for &position in &shots(seed) {
+                assert_eq!(bit_board.shoot(position), board.shoot(position));
+                assert_eq!(
+                    bit_board.reveal_around_ship(position),
+                    board.reveal_around_ship(position)
+                );
+                assert_eq!(bit_board.to_string(), board.to_string());
+                assert_eq!(bit_board.hits_left(), board.hits_left());
+                assert_eq!(bit_board.ships_left(), board.ships_left());
+                assert_eq!(
+                    bit_board.reveal_ship_cell(&mut bit_random),
+                    board.reveal_ship_cell(&mut random)
+                );
+                assert_eq!(bit_board.shoot(position), GameBoardShotResult::Miss);
+            }
+            assert_eq!((bit_board.hits_left(), bit_board.ships_left()), (0, 0));
+        }
+    }
+}
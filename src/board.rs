@@ -1,18 +1,22 @@
-use std::convert::TryFrom;
-
 use futures_util::stream::StreamExt as _;
 use tokio::codec::FramedRead;
 use tokio::stream::StreamExt as _;
 
+use crate::bitboard;
 use crate::cells::{GameBoardCell, GameBoardCellState};
-use crate::position::Position;
+use crate::layout::Layout;
+use crate::position::{Position, Symmetry};
+use crate::random::Random;
+use crate::rules::GameRules;
+use crate::ship::Ship;
 use crate::{InvalidInputError, GAME_BOARD_SIZE};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum GameBoardShotResult {
     Miss,
     Hit,
     Sunk,
+    Mine,
 }
 
 impl GameBoardShotResult {
@@ -21,14 +25,86 @@ impl GameBoardShotResult {
             Self::Miss => "miss",
             Self::Hit => "hit",
             Self::Sunk => "sunk",
+            Self::Mine => "mine",
+        }
+    }
+}
+
+/// Result of a shot as it is reported to the shooter and written into the game log, e.g.
+/// `miss`, `sunk 1 1 1 2` if the rules reveal the cells of sunk ships, or `mine 5 5` if the
+/// shooter's ship cell is revealed to the opponent as a penalty for hitting a mine.
+#[derive(Debug, Clone)]
+pub struct ShotReport {
+    pub result: GameBoardShotResult,
+    pub sunk_ship: Vec<Position>,
+    pub revealed_cell: Option<Position>,
+}
+
+impl From<GameBoardShotResult> for ShotReport {
+    fn from(result: GameBoardShotResult) -> Self {
+        Self {
+            result,
+            sunk_ship: Vec::new(),
+            revealed_cell: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ShotReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.result.as_str())?;
+        for position in self.sunk_ship.iter().chain(&self.revealed_cell) {
+            write!(f, " {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for GameBoardShotResult {
+    type Err = InvalidInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "miss" => Ok(Self::Miss),
+            "hit" => Ok(Self::Hit),
+            "sunk" => Ok(Self::Sunk),
+            "mine" => Ok(Self::Mine),
+            _ => Err(InvalidInputError {}),
+        }
+    }
+}
+
+/// Parses the shot report back from the way it is displayed (e.g. in the game log).
+impl std::str::FromStr for ShotReport {
+    type Err = InvalidInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (result, positions) = match s.find(' ') {
+            Some(index) => (&s[..index], Position::parse_list(&s[index..])?),
+            None => (s, Vec::new()),
+        };
+        let mut shot_report = Self::from(result.parse::<GameBoardShotResult>()?);
+        match (shot_report.result, positions.len()) {
+            (_, 0) => (),
+            (GameBoardShotResult::Sunk, _) => shot_report.sunk_ship = positions,
+            (GameBoardShotResult::Mine, 1) => shot_report.revealed_cell = Some(positions[0]),
+            _ => return Err(InvalidInputError {}),
         }
+        Ok(shot_report)
     }
 }
 
 #[derive(Clone)]
 pub struct GameBoard {
     inner: arrayvec::ArrayVec<[GameBoardCell; (GAME_BOARD_SIZE * GAME_BOARD_SIZE) as usize]>,
+    /// Index of the ship in `ships` for every cell occupied by a ship.
+    ship_ids: [Option<u8>; (GAME_BOARD_SIZE * GAME_BOARD_SIZE) as usize],
+    ships: Vec<Ship>,
+    /// Ship cells revealed to the opponent as a penalty for hitting mines.
+    revealed_cells: Vec<Position>,
     hits_left: u8,
+    ships_left: u8,
 }
 
 impl GameBoard {
@@ -43,7 +119,10 @@ impl GameBoard {
         &mut self.inner[Self::get_index(pos)]
     }
 
-    pub async fn read<T, D>(reader: &mut FramedRead<T, D>) -> Result<Self, InvalidInputError>
+    pub async fn read<T, D>(
+        reader: &mut FramedRead<T, D>,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
     where
         T: tokio::io::AsyncRead + Unpin,
         D: tokio::codec::Decoder + Unpin,
@@ -51,117 +130,92 @@ impl GameBoard {
     {
         let mut player_map_stream = reader.chunks(10).timeout(std::time::Duration::from_secs(1));
         if let Some(Ok(lines)) = player_map_stream.next().await {
-            GameBoard::from_lines(lines.into_iter().filter_map(|line| line.ok()))
+            GameBoard::from_lines_with_rules(lines.into_iter().filter_map(|line| line.ok()), rules)
         } else {
             Err(InvalidInputError {})
         }
     }
 
+    /// Parses and validates the board by the default rules (the classic fleet without mines).
+    #[cfg(test)]
     pub fn from_lines<Item, I>(lines: I) -> Result<Self, InvalidInputError>
     where
         Item: AsRef<str>,
         I: Iterator<Item = Item>,
     {
-        let mut ships_count: [u8; 5] = [0, 4, 3, 2, 1];
-        let hits_left = ships_count
-            .iter()
-            .zip(0..5)
-            .map(|(ship_size, count)| ship_size * count)
-            .sum();
-
-        // Parse string map into `GameBoardCell`s and validate shape
-        let board = Self {
-            inner: lines
-                .take(10)
-                .flat_map(|line| {
-                    if line.as_ref().len() == usize::from(GAME_BOARD_SIZE) {
-                        line.as_ref().chars().map(GameBoardCell::try_from).collect()
-                    } else {
-                        arrayvec::ArrayVec::from(
-                            [Err(InvalidInputError {}); GAME_BOARD_SIZE as usize],
-                        )
-                    }
-                })
-                .collect::<Result<_, _>>()?,
-            hits_left,
-        };
-
-        if board.inner.len() != usize::from(GAME_BOARD_SIZE * GAME_BOARD_SIZE) {
-            return Err(InvalidInputError {});
-        }
+        Self::from_lines_with_rules(lines, &GameRules::default())
+    }
 
-        // Validate amount of ships and their shape
+    /// Parses and validates the board. Ship cells are marked either with `#`, in which case the
+    /// adjacent `#` cells form a single ship, or with a letter, in which case all the cells
+    /// marked with the same letter form a single ship, so ships can touch each other when the
+    /// placement rule allows that.
+    pub fn from_lines_with_rules<Item, I>(
+        lines: I,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
+    where
+        Item: AsRef<str>,
+        I: Iterator<Item = Item>,
+    {
+        let layout = Layout::from_lines_with_rules(lines, rules)?;
+        let mut board = Self {
+            inner: arrayvec::ArrayVec::new(),
+            ship_ids: [None; (GAME_BOARD_SIZE * GAME_BOARD_SIZE) as usize],
+            ships: Vec::new(),
+            revealed_cells: Vec::new(),
+            hits_left: 0,
+            ships_left: 0,
+        };
+        let ships_mask = layout
+            .ships
+            .iter()
+            .fold(0, |ships, &(_, ship)| ships | ship);
         for position in Position::top_left().iter() {
-            if let Some(position_above) = position.get_above() {
-                // Detect ships that are touching by their corners
-                if !board.get(position).is_empty()
-                    && ![position_above.get_left(), position_above.get_right()]
-                        .iter()
-                        .flatten()
-                        .all(|&pos| board.get(pos).is_empty())
-                {
-                    return Err(InvalidInputError {});
-                }
-
-                // If there is a non-empty cell above the current cell, we skip further checking as
-                // we should have already accounted this cell when handling that one.
-                if !board.get(position_above).is_empty() {
-                    continue;
-                }
-            }
-            // If there is a non-empty cell on the left of the current cell, we skip further
-            // checking as we should have already accounted this cell when handling that one.
-            if let Some(false) = position.get_left().map(|pos| board.get(pos).is_empty()) {
-                continue;
+            board
+                .inner
+                .push(if ships_mask & bitboard::bit(position) != 0 {
+                    GameBoardCell::Ship(GameBoardCellState::NonShot)
+                } else if layout.mines & bitboard::bit(position) != 0 {
+                    GameBoardCell::Mine(GameBoardCellState::NonShot)
+                } else {
+                    GameBoardCell::Empty(GameBoardCellState::NonShot)
+                });
+        }
+        for (ship_id, (label, ship)) in layout.ships.into_iter().enumerate() {
+            let cells: Vec<Position> = bitboard::positions(ship).collect();
+            for &cell in &cells {
+                board.ship_ids[Self::get_index(cell)] = Some(ship_id as u8);
             }
-
-            let vertical_ship_size = position
-                .iter_below()
-                .take_while(|&pos| board.get(pos).is_ship())
-                .count();
-            let horizontal_ship_size = position
-                .iter_right()
-                .take_while(|&pos| board.get(pos).is_ship())
-                .count();
-
-            let ship_size = if vertical_ship_size == horizontal_ship_size {
-                match vertical_ship_size {
-                    0 => continue,
-                    1 => 1,
-                    _ => return Err(InvalidInputError {}),
-                }
-            } else if vertical_ship_size == 1 {
-                horizontal_ship_size
-            } else if horizontal_ship_size == 1 {
-                vertical_ship_size
-            } else {
-                return Err(InvalidInputError {});
-            };
-
-            let ship_count = ships_count
-                .get_mut(ship_size)
-                .ok_or_else(|| InvalidInputError {})?;
-            *ship_count = ship_count
-                .checked_sub(1)
-                .ok_or_else(|| InvalidInputError {})?;
+            let ship = Ship::new(label, cells)?;
+            board.hits_left += ship.size();
+            board.ships_left += 1;
+            board.ships.push(ship);
         }
+        Ok(board)
+    }
 
-        if ships_count.iter().sum::<u8>() > 0 {
-            Err(InvalidInputError {})
-        } else {
-            Ok(board)
-        }
+    fn ship_id(&self, position: Position) -> Option<u8> {
+        self.ship_ids[Self::get_index(position)]
     }
 
+    /// Renders the board the same way it is sent by players, except that the shot cells are
+    /// marked with `O` (empty) and `$` (ship).
     pub fn to_string(&self) -> String {
-        self.inner
-            .chunks(usize::from(GAME_BOARD_SIZE))
-            .map(|line| {
-                line.iter()
-                    .copied()
-                    .map(GameBoardCell::into)
-                    .chain("\n".chars())
-                    .collect::<String>()
+        Position::top_left()
+            .iter()
+            .map(|position| {
+                let cell = match (self.get(position), self.ship_id(position)) {
+                    (GameBoardCell::Ship(GameBoardCellState::NonShot), Some(ship_id)) => {
+                        self.ships[usize::from(ship_id)].label()
+                    }
+                    (cell, _) => cell.into(),
+                };
+                if position.x() == GAME_BOARD_SIZE - 1 {
+                    format!("{}\n", cell)
+                } else {
+                    cell.to_string()
+                }
             })
             .collect()
     }
@@ -171,25 +225,19 @@ impl GameBoard {
         let shot_result = if let GameBoardCell::Ship(GameBoardCellState::NonShot) = cell {
             *cell = GameBoardCell::Ship(GameBoardCellState::Shot);
 
-            fn iter_helper<'a>(
-                board: &'a GameBoard,
-                iter: impl Iterator<Item = Position> + 'a,
-            ) -> impl Iterator<Item = GameBoardCell> + 'a {
-                iter.skip(1)
-                    .map(move |pos| board.get(pos))
-                    .take_while(|cell| cell.is_ship())
-            }
-
-            if iter_helper(self, position.iter_left())
-                .chain(iter_helper(self, position.iter_right()))
-                .chain(iter_helper(self, position.iter_above()))
-                .chain(iter_helper(self, position.iter_below()))
-                .all(|cell| cell.is_shot())
-            {
+            let ship_id = self
+                .ship_id(position)
+                .expect("every ship cell belongs to a ship");
+            let ship = &mut self.ships[usize::from(ship_id)];
+            ship.hit();
+            if ship.is_sunk() {
                 GameBoardShotResult::Sunk
             } else {
                 GameBoardShotResult::Hit
             }
+        } else if let GameBoardCell::Mine(GameBoardCellState::NonShot) = cell {
+            *cell = GameBoardCell::Mine(GameBoardCellState::Shot);
+            GameBoardShotResult::Mine
         } else {
             if let GameBoardCell::Empty(GameBoardCellState::NonShot) = cell {
                 *cell = GameBoardCell::Empty(GameBoardCellState::Shot);
@@ -199,17 +247,163 @@ impl GameBoard {
         if let GameBoardShotResult::Hit | GameBoardShotResult::Sunk = shot_result {
             self.hits_left -= 1;
         }
+        if let GameBoardShotResult::Sunk = shot_result {
+            self.ships_left -= 1;
+        }
         shot_result
     }
 
+    pub fn ship_at(&self, position: Position) -> Option<&Ship> {
+        self.ship_id(position)
+            .map(|ship_id| &self.ships[usize::from(ship_id)])
+    }
+
+    pub fn ships(&self) -> &[Ship] {
+        &self.ships
+    }
+
+    /// Marks all the cells around the ship at the given position as shot since no other ship can
+    /// be there. Returns positions of the ship cells.
+    pub fn reveal_around_ship(&mut self, position: Position) -> Vec<Position> {
+        let ship = self
+            .ship_at(position)
+            .map(|ship| ship.cells().to_vec())
+            .unwrap_or_default();
+        for neighbour in ship.iter().flat_map(|pos| pos.neighbours()) {
+            let cell = self.get_mut(neighbour);
+            if let GameBoardCell::Empty(GameBoardCellState::NonShot) = cell {
+                *cell = GameBoardCell::Empty(GameBoardCellState::Shot);
+            }
+        }
+        ship
+    }
+
+    /// The same board (with the same shots) rotated or mirrored by the `symmetry`. The rules
+    /// that accept a board accept all its transformations as well.
+    pub fn transform(&self, symmetry: Symmetry) -> Self {
+        let mut board = self.clone();
+        let inverse = symmetry.inverse();
+        for position in Position::top_left().iter() {
+            let index = Self::get_index(position);
+            board.inner[index] = self.get(position.transform(inverse));
+            board.ship_ids[index] = self.ship_id(position.transform(inverse));
+        }
+        board.ships = self
+            .ships
+            .iter()
+            .map(|ship| ship.transform(symmetry))
+            .collect();
+        board.revealed_cells = self
+            .revealed_cells
+            .iter()
+            .map(|position| position.transform(symmetry))
+            .collect();
+        board
+    }
+
+    /// Picks a random ship cell that has been neither shot nor revealed yet to reveal it to the
+    /// opponent.
+    pub fn reveal_ship_cell(&mut self, random: &mut Random) -> Option<Position> {
+        let cells: Vec<Position> = Position::top_left()
+            .iter()
+            .filter(|&position| {
+                self.get(position).is_ship()
+                    && !self.get(position).is_shot()
+                    && !self.revealed_cells.contains(&position)
+            })
+            .collect();
+        if cells.is_empty() {
+            return None;
+        }
+        let position = cells[random.below(cells.len())];
+        self.revealed_cells.push(position);
+        Some(position)
+    }
+
+    /// Marks the ship cell as revealed to the opponent, e.g. when a game is replayed from its log.
+    pub fn mark_revealed(&mut self, position: Position) {
+        if !self.revealed_cells.contains(&position) {
+            self.revealed_cells.push(position);
+        }
+    }
+
     pub fn hits_left(&self) -> u8 {
         self.hits_left
     }
+
+    pub fn ships_left(&self) -> u8 {
+        self.ships_left
+    }
 }
 
 impl std::fmt::Debug for GameBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "GameBoard ({} hits left):\n{}", self.hits_left, self.to_string())
+        write!(
+            f,
+            "GameBoard ({} hits left, {} ships left):\n{}",
+            self.hits_left,
+            self.ships_left,
+            self.to_string()
+        )?;
+        for ship in self.ships().iter().filter(|ship| !ship.is_sunk()) {
+            writeln!(f, "{} ({} of {} cells hit)", ship, ship.hits(), ship.size())?;
+        }
+        Ok(())
+    }
+}
+
+/// The operations of a player's board the game is played with, shared by `GameBoard` and the
+/// faster `BitBoard`, so that the same code can drive either of them.
+pub trait Board: Clone {
+    fn from_lines_with_rules<Item, I>(
+        lines: I,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
+    where
+        Item: AsRef<str>,
+        I: Iterator<Item = Item>;
+    fn get(&self, position: Position) -> GameBoardCell;
+    fn shoot(&mut self, position: Position) -> GameBoardShotResult;
+    fn reveal_around_ship(&mut self, position: Position) -> Vec<Position>;
+    fn reveal_ship_cell(&mut self, random: &mut Random) -> Option<Position>;
+    fn hits_left(&self) -> u8;
+    fn ships_left(&self) -> u8;
+}
+
+impl Board for GameBoard {
+    fn from_lines_with_rules<Item, I>(
+        lines: I,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
+    where
+        Item: AsRef<str>,
+        I: Iterator<Item = Item>,
+    {
+        GameBoard::from_lines_with_rules(lines, rules)
+    }
+
+    fn get(&self, position: Position) -> GameBoardCell {
+        GameBoard::get(self, position)
+    }
+
+    fn shoot(&mut self, position: Position) -> GameBoardShotResult {
+        GameBoard::shoot(self, position)
+    }
+
+    fn reveal_around_ship(&mut self, position: Position) -> Vec<Position> {
+        GameBoard::reveal_around_ship(self, position)
+    }
+
+    fn reveal_ship_cell(&mut self, random: &mut Random) -> Option<Position> {
+        GameBoard::reveal_ship_cell(self, random)
+    }
+
+    fn hits_left(&self) -> u8 {
+        GameBoard::hits_left(self)
+    }
+
+    fn ships_left(&self) -> u8 {
+        GameBoard::ships_left(self)
     }
 }
 
@@ -218,6 +412,17 @@ mod tests {
     #![allow(non_snake_case)]
 
     use super::*;
+    use crate::rules::PlacementRule;
+
+    #[test]
+    fn test_ShotReport_from_str() {
+        for &line in &["miss", "hit", "sunk", "sunk 1 1 2 1", "mine", "mine 5 5"] {
+            assert_eq!(line.parse::<ShotReport>().unwrap().to_string(), line);
+        }
+        for &line in &["", "shot", "hit 1 1", "mine 1 1 1 2", "sunk 1", "sunk 1 11"] {
+            assert!(line.parse::<ShotReport>().is_err());
+        }
+    }
 
     #[test]
     fn test_GameBoard_validation_ok() {
@@ -239,6 +444,366 @@ mod tests {
         .is_ok());
     }
 
+    #[test]
+    fn test_GameBoard_validation_ignores_trailing_whitespace() {
+        let lines = [
+            "####_###_#\r",
+            "_________# ",
+            "_________#\t",
+            "__________  \r",
+            "_________#",
+            "_________#",
+            "__________",
+            "#________#",
+            "_________#",
+            "#_#_#_##__\r",
+        ];
+        assert!(GameBoard::from_lines(lines.iter()).is_ok());
+
+        let lines = [
+            " ####_###_#",
+            "_________#",
+            "_________#",
+            "__________",
+            "_________#",
+            "_________#",
+            "__________",
+            "#________#",
+            "_________#",
+            "#_#_#_##__",
+        ];
+        assert!(GameBoard::from_lines(lines.iter()).is_err());
+
+        let lines = [
+            "####_###_# #",
+            "_________#",
+            "_________#",
+            "__________",
+            "_________#",
+            "_________#",
+            "__________",
+            "#________#",
+            "_________#",
+            "#_#_#_##__",
+        ];
+        assert!(GameBoard::from_lines(lines.iter()).is_err());
+    }
+
+    #[test]
+    fn test_GameBoard_shoot() {
+        let mut board = GameBoard::from_lines(
+            "\
+             ####______ \
+             __________ \
+             ###_###___ \
+             __________ \
+             ##_##_##__ \
+             __________ \
+             #_#_#_#___ \
+             __________ \
+             __________ \
+             __________ \
+             "
+            .split_whitespace(),
+        )
+        .unwrap();
+        assert_eq!((board.hits_left(), board.ships_left()), (20, 10));
+
+        let shoot = |board: &mut GameBoard, position: &str| {
+            board.shoot(position.parse().unwrap()).as_str().to_owned()
+        };
+        assert_eq!(shoot(&mut board, "5 1"), "miss");
+        assert_eq!(shoot(&mut board, "2 1"), "hit");
+        assert_eq!(shoot(&mut board, "2 1"), "miss");
+        assert_eq!(shoot(&mut board, "1 1"), "hit");
+        assert_eq!(shoot(&mut board, "4 1"), "hit");
+        assert_eq!((board.hits_left(), board.ships_left()), (17, 10));
+        assert_eq!(shoot(&mut board, "3 1"), "sunk");
+        assert_eq!(shoot(&mut board, "1 7"), "sunk");
+        assert_eq!((board.hits_left(), board.ships_left()), (15, 8));
+
+        let ship = board.ship_at("3 1".parse().unwrap()).unwrap();
+        assert_eq!((ship.size(), ship.hits()), (4, 4));
+        assert_eq!(ship.to_string(), "4-cell horizontal ship at 1 1");
+        let ship = board.ship_at("5 3".parse().unwrap()).unwrap();
+        assert_eq!((ship.size(), ship.hits()), (3, 0));
+        assert_eq!(
+            board.ships().iter().filter(|ship| ship.is_sunk()).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_GameBoard_reveal_around_ship() {
+        let mut board = GameBoard::from_lines(
+            "\
+             ####______ \
+             __________ \
+             ###_###___ \
+             __________ \
+             ##_##_##__ \
+             __________ \
+             #_#_#_#___ \
+             __________ \
+             __________ \
+             __________ \
+             "
+            .split_whitespace(),
+        )
+        .unwrap();
+        let ship: Vec<String> = board
+            .reveal_around_ship("2 3".parse().unwrap())
+            .iter()
+            .map(Position::to_string)
+            .collect();
+        assert_eq!(ship, vec!["1 3", "2 3", "3 3"]);
+        assert!(board.ship_at("4 3".parse().unwrap()).is_none());
+        assert_eq!(
+            board.to_string(),
+            "\
+             ####______\n\
+             OOOO______\n\
+             ###O###___\n\
+             OOOO______\n\
+             ##_##_##__\n\
+             __________\n\
+             #_#_#_#___\n\
+             __________\n\
+             __________\n\
+             __________\n\
+             "
+        );
+        assert_eq!((board.hits_left(), board.ships_left()), (20, 10));
+    }
+
+    fn rules_with_placement(placement: PlacementRule) -> GameRules {
+        GameRules {
+            placement,
+            ..GameRules::default()
+        }
+    }
+
+    #[test]
+    fn test_GameBoard_validation_of_ships_touching_by_corners() {
+        let lines = "\
+                     ####______ \
+                     ____###___ \
+                     _______##_ \
+                     ###______# \
+                     ___##_____ \
+                     _____#____ \
+                     ______#___ \
+                     _______#__ \
+                     ________## \
+                     __________ \
+                     ";
+        assert!(GameBoard::from_lines(lines.split_whitespace()).is_err());
+        assert!(GameBoard::from_lines_with_rules(
+            lines.split_whitespace(),
+            &rules_with_placement(PlacementRule::Corners)
+        )
+        .is_ok());
+        assert!(GameBoard::from_lines_with_rules(
+            lines.split_whitespace(),
+            &rules_with_placement(PlacementRule::Any)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_GameBoard_validation_of_ships_touching_by_edges() {
+        let lines = "\
+                     AAAABBB___ \
+                     CCCDD_____ \
+                     EE_FF_____ \
+                     HIJK______ \
+                     __________ \
+                     __________ \
+                     __________ \
+                     __________ \
+                     __________ \
+                     __________ \
+                     ";
+        assert!(GameBoard::from_lines(lines.split_whitespace()).is_err());
+        assert!(GameBoard::from_lines_with_rules(
+            lines.split_whitespace(),
+            &rules_with_placement(PlacementRule::Corners)
+        )
+        .is_err());
+        let mut board = GameBoard::from_lines_with_rules(
+            lines.split_whitespace(),
+            &rules_with_placement(PlacementRule::Any),
+        )
+        .unwrap();
+        assert_eq!((board.hits_left(), board.ships_left()), (20, 10));
+        assert_eq!(board.to_string().lines().next(), Some("AAAABBB___"));
+        assert_eq!(board.shoot("1 3".parse().unwrap()).as_str(), "hit");
+        assert_eq!(board.shoot("2 3".parse().unwrap()).as_str(), "sunk");
+        assert_eq!(board.shoot("1 4".parse().unwrap()).as_str(), "sunk");
+        let ship = board.ship_at("4 3".parse().unwrap()).unwrap();
+        assert_eq!((ship.label(), ship.size(), ship.hits()), ('F', 2, 0));
+
+        // The same letter cannot mark different ships
+        assert!(GameBoard::from_lines_with_rules(
+            "\
+             AAAABBB___ \
+             CCCDD_____ \
+             EE_FF_____ \
+             HIJK______ \
+             __________ \
+             ________HH \
+             __________ \
+             __________ \
+             __________ \
+             __________ \
+             "
+            .split_whitespace(),
+            &rules_with_placement(PlacementRule::Any)
+        )
+        .is_err());
+
+        // Adjacent `#` cells still form a single ship
+        assert!(GameBoard::from_lines_with_rules(
+            "\
+             ####______ \
+             ###_______ \
+             ___###____ \
+             ##________ \
+             ___##_____ \
+             _____##___ \
+             #_#_#_#___ \
+             __________ \
+             __________ \
+             __________ \
+             "
+            .split_whitespace(),
+            &rules_with_placement(PlacementRule::Any)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_GameBoard_validation_of_polyomino_fleet() {
+        let rules = GameRules {
+            fleet: "1x##/##,2x#_/#_/##,1x###/_#_,2x#".parse().unwrap(),
+            ..GameRules::default()
+        };
+        let lines = "\
+                     ##__#_____ \
+                     ##__#_____ \
+                     ____##____ \
+                     #_________ \
+                     ______###_ \
+                     _###___#__ \
+                     ___#______ \
+                     _________# \
+                     __________ \
+                     __________ \
+                     ";
+        let mut board = GameBoard::from_lines_with_rules(lines.split_whitespace(), &rules).unwrap();
+        assert_eq!((board.hits_left(), board.ships_left()), (18, 6));
+        assert!(GameBoard::from_lines(lines.split_whitespace()).is_err());
+
+        let shoot = |board: &mut GameBoard, position: &str| {
+            board.shoot(position.parse().unwrap()).as_str().to_owned()
+        };
+        assert_eq!(shoot(&mut board, "5 1"), "hit");
+        assert_eq!(shoot(&mut board, "5 2"), "hit");
+        assert_eq!(shoot(&mut board, "5 3"), "hit");
+        assert_eq!(shoot(&mut board, "6 3"), "sunk");
+        assert_eq!(shoot(&mut board, "2 6"), "hit");
+        assert_eq!(shoot(&mut board, "3 6"), "hit");
+        assert_eq!(shoot(&mut board, "4 7"), "hit");
+        assert_eq!(shoot(&mut board, "4 6"), "sunk");
+        assert_eq!((board.hits_left(), board.ships_left()), (10, 4));
+
+        // Too many ships of the same shape
+        assert!(GameBoard::from_lines_with_rules(
+            "\
+             ##__#_____ \
+             ##__#_____ \
+             ____##____ \
+             #_________ \
+             ______###_ \
+             _###___#__ \
+             ___#______ \
+             _________# \
+             __________ \
+             ###_______ \
+             "
+            .split_whitespace(),
+            &rules
+        )
+        .is_err());
+
+        // Shapes that are not in the fleet
+        assert!(GameBoard::from_lines_with_rules(
+            "\
+             ##__#_____ \
+             ##__#_____ \
+             ____##____ \
+             #_________ \
+             ______###_ \
+             _##____#__ \
+             __##______ \
+             _________# \
+             __________ \
+             __________ \
+             "
+            .split_whitespace(),
+            &rules
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_GameBoard_mines() {
+        let rules = GameRules {
+            mines: 2,
+            ..GameRules::default()
+        };
+        let lines = "\
+                     ####_____* \
+                     __________ \
+                     ###_###___ \
+                     __________ \
+                     ##_##_##__ \
+                     __________ \
+                     #_#_#_#___ \
+                     ________*_ \
+                     __________ \
+                     __________ \
+                     ";
+        assert!(GameBoard::from_lines(lines.split_whitespace()).is_err());
+        let mut board = GameBoard::from_lines_with_rules(lines.split_whitespace(), &rules).unwrap();
+        assert_eq!(board.to_string().lines().next(), Some("####_____*"));
+
+        let shoot = |board: &mut GameBoard, position: &str| {
+            board.shoot(position.parse().unwrap()).as_str().to_owned()
+        };
+        assert_eq!(shoot(&mut board, "10 1"), "mine");
+        assert_eq!(shoot(&mut board, "10 1"), "miss");
+        assert_eq!(board.to_string().lines().next(), Some("####_____X"));
+        assert_eq!((board.hits_left(), board.ships_left()), (20, 10));
+
+        assert_eq!(shoot(&mut board, "1 1"), "hit");
+        let mut random = Random::new(0);
+        let revealed_cells: Vec<Position> =
+            std::iter::from_fn(|| board.reveal_ship_cell(&mut random)).collect();
+        assert_eq!(revealed_cells.len(), 19);
+        assert!(!revealed_cells.contains(&"1 1".parse().unwrap()));
+        assert!(revealed_cells
+            .iter()
+            .all(|&position| board.get(position).is_ship()));
+        assert_ne!(revealed_cells[0].to_string(), "2 1");
+
+        let rules = GameRules {
+            mines: 3,
+            ..GameRules::default()
+        };
+        assert!(GameBoard::from_lines_with_rules(lines.split_whitespace(), &rules).is_err());
+    }
+
     #[test]
     fn test_GameBoard_validation_of_invalid_size() {
         assert!(GameBoard::from_lines("".split_whitespace()).is_err());
@@ -379,6 +944,25 @@ mod tests {
             .split_whitespace()
         )
         .is_err());
+
+        // `O` and `X` mark shot cells in the rendered boards, so they cannot label ships
+        let lines = "\
+                     AAAA______ \
+                     __________ \
+                     ###_###___ \
+                     __________ \
+                     ##_##_##__ \
+                     __________ \
+                     #_#_#_#___ \
+                     __________ \
+                     __________ \
+                     __________ \
+                     ";
+        assert!(GameBoard::from_lines(lines.split_whitespace()).is_ok());
+        for label in &["O", "X"] {
+            let lines = lines.replace("A", label);
+            assert!(GameBoard::from_lines(lines.split_whitespace()).is_err());
+        }
     }
 
     #[test]
@@ -520,6 +1104,132 @@ mod tests {
         .is_err());
     }
 
+    /// Rotates or mirrors the board as it is sent by players.
+    fn transform_lines(lines: &str, symmetry: Symmetry) -> Vec<String> {
+        let labels: Vec<char> = lines.split_whitespace().flat_map(str::chars).collect();
+        let mut transformed = labels.clone();
+        for position in Position::top_left().iter() {
+            transformed[usize::from(position.transform(symmetry).yx())] =
+                labels[usize::from(position.yx())];
+        }
+        transformed
+            .chunks(usize::from(GAME_BOARD_SIZE))
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_GameBoard_validation_is_invariant_under_symmetries() {
+        let polyomino_rules = GameRules {
+            fleet: "1x##/##,2x#_/#_/##,1x###/_#_,2x#".parse().unwrap(),
+            ..GameRules::default()
+        };
+        let cases = [
+            (
+                "####_###_# _________# _________# __________ _________# \
+                 _________# __________ #________# _________# #_#_#_##__",
+                GameRules::default(),
+                true,
+            ),
+            (
+                "##__#_____ ##__#_____ ____##____ #_________ ______###_ \
+                 _###___#__ ___#______ _________# __________ __________",
+                polyomino_rules.clone(),
+                true,
+            ),
+            (
+                "##__#_____ ##__#_____ ____##____ #_________ ______###_ \
+                 _##____#__ __##______ _________# __________ __________",
+                polyomino_rules,
+                false,
+            ),
+            (
+                "####______ ____###___ _______##_ ###______# ___##_____ \
+                 _____#____ ______#___ _______#__ ________## __________",
+                rules_with_placement(PlacementRule::Corners),
+                true,
+            ),
+            (
+                "####______ ____###___ _______##_ ###______# ___##_____ \
+                 _____#____ ______#___ _______#__ ________## __________",
+                GameRules::default(),
+                false,
+            ),
+            (
+                "AAAABBB___ CCCDD_____ EE_FF_____ HIJK______ __________ \
+                 __________ __________ __________ __________ __________",
+                rules_with_placement(PlacementRule::Any),
+                true,
+            ),
+            (
+                "####______ ___#______ ___#______ __________ ###_______ \
+                 __________ ##_##_##__ __________ #_#_#_#___ __________",
+                GameRules::default(),
+                false,
+            ),
+        ];
+        for (lines, rules, is_valid) in cases.iter() {
+            let board = GameBoard::from_lines_with_rules(lines.split_whitespace(), rules);
+            assert_eq!(board.is_ok(), *is_valid, "{}", lines);
+            for &symmetry in &Symmetry::ALL {
+                let transformed_lines = transform_lines(lines, symmetry);
+                let transformed_board =
+                    GameBoard::from_lines_with_rules(transformed_lines.iter(), rules);
+                assert_eq!(transformed_board.is_ok(), *is_valid, "{:?}", symmetry);
+                if let (Ok(board), Ok(transformed_board)) = (&board, transformed_board) {
+                    let board = board.transform(symmetry);
+                    assert_eq!(board.to_string(), transformed_board.to_string());
+                    assert_eq!(board.hits_left(), transformed_board.hits_left());
+                    assert_eq!(board.ships_left(), transformed_board.ships_left());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_GameBoard_transform() {
+        let mut board = GameBoard::from_lines(
+            "####______ __________ ###_###___ __________ ##_##_##__ \
+             __________ #_#_#_#___ __________ __________ __________"
+                .split_whitespace(),
+        )
+        .unwrap();
+        board.shoot("1 1".parse().unwrap());
+        board.shoot("5 5".parse().unwrap());
+        board.shoot("1 7".parse().unwrap());
+        board.reveal_ship_cell(&mut Random::new(0));
+        for &symmetry in &Symmetry::ALL {
+            let mut transformed = board.transform(symmetry);
+            assert_eq!((transformed.hits_left(), transformed.ships_left()), (17, 9));
+            for position in Position::top_left().iter() {
+                let transformed_position = position.transform(symmetry);
+                assert_eq!(
+                    transformed.get(transformed_position).is_shot(),
+                    board.get(position).is_shot()
+                );
+                assert_eq!(
+                    transformed.ship_at(transformed_position).map(Ship::size),
+                    board.ship_at(position).map(Ship::size)
+                );
+            }
+            assert_eq!(
+                transformed.transform(symmetry.inverse()).to_string(),
+                board.to_string()
+            );
+            // The hit ship keeps its hits, and the revealed cell is not revealed again
+            let reveals_count = |board: &mut GameBoard| {
+                let mut random = Random::new(0);
+                std::iter::from_fn(|| board.reveal_ship_cell(&mut random)).count()
+            };
+            assert_eq!(
+                reveals_count(&mut transformed.clone()),
+                reveals_count(&mut board.clone())
+            );
+            let position = "2 1".parse::<Position>().unwrap().transform(symmetry);
+            assert_eq!(transformed.shoot(position).as_str(), "hit");
+        }
+    }
+
     #[test]
     fn test_GameBoard_validation_of_invalid_ship_shapes() {
         assert!(GameBoard::from_lines(
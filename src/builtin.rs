@@ -0,0 +1,314 @@
+use crate::bitboard::{self, Mask};
+use crate::board::{Board, GameBoardShotResult, ShotReport};
+use crate::placement;
+use crate::position::Position;
+use crate::random::Random;
+use crate::rules::{GameRules, PlacementRule};
+use crate::GAME_BOARD_SIZE;
+
+/// A way to pick shots played in-process, without a bot behind it, e.g. by the simulator.
+pub trait Strategy {
+    /// Returns `None` if the strategy gives up, which loses the game.
+    fn next_shot(&mut self) -> Option<Position>;
+
+    /// Picks the shots of a salvo. By default, they are the next shots one by one.
+    fn next_volley(&mut self, size: u8) -> Option<Vec<Position>> {
+        (0..size).map(|_| self.next_shot()).collect()
+    }
+
+    /// Tells the result of the shot at the `position`.
+    fn shot_result(&mut self, _position: Position, _shot_report: &ShotReport) {}
+
+    /// Tells the cell of an opponent's ship revealed as a penalty for hitting a mine.
+    fn revealed_cell(&mut self, _position: Position) {}
+}
+
+/// Shoots at random cells it has not shot yet. The same seed gives the same order of shots.
+pub struct RandomStrategy {
+    random: Random,
+    /// The cells that have not been shot yet.
+    targets: Vec<Position>,
+}
+
+impl RandomStrategy {
+    pub fn new(random: Random) -> Self {
+        Self {
+            random,
+            targets: Position::top_left().iter().collect(),
+        }
+    }
+}
+
+impl Strategy for RandomStrategy {
+    /// Returns `None` once every cell has been shot.
+    fn next_shot(&mut self) -> Option<Position> {
+        if self.targets.is_empty() {
+            return None;
+        }
+        let index = self.random.below(self.targets.len());
+        Some(self.targets.swap_remove(index))
+    }
+}
+
+/// Hunts for ships at random cells and, once a ship is hit, targets the cells around the hit
+/// until the ship is sunk. While hunting, only the cells of every `parity`-th diagonal are shot,
+/// since every ship of at least `parity` cells in a row crosses one of them.
+pub struct HuntStrategy {
+    random: Random,
+    placement: PlacementRule,
+    /// The cells of every `parity`-th diagonal.
+    hunting_cells: Mask,
+    /// The cells that have been shot or cannot have a ship.
+    shot: Mask,
+    /// The hit cells of the ships that are not sunk yet.
+    hits: Mask,
+    /// The cells next to the hits to shoot at before hunting further.
+    targets: Vec<Position>,
+}
+
+impl HuntStrategy {
+    pub fn new(rules: &GameRules, parity: u8, random: Random) -> Self {
+        let parity = std::cmp::max(parity, 1);
+        let hunting_cells = Position::top_left()
+            .iter()
+            .filter(|position| (position.x() + position.y()) % parity == 0)
+            .fold(0, |cells, position| cells | bitboard::bit(position));
+        Self {
+            random,
+            placement: rules.placement,
+            hunting_cells,
+            shot: 0,
+            hits: 0,
+            targets: Vec::new(),
+        }
+    }
+
+    fn is_shot(&self, position: Position) -> bool {
+        self.shot & bitboard::bit(position) != 0
+    }
+
+    /// Picks a random cell that has not been shot yet, preferring the hunting cells.
+    fn hunt(&mut self) -> Option<Position> {
+        let not_shot = bitboard::ALL_CELLS & !self.shot;
+        let candidates = match not_shot & self.hunting_cells {
+            0 => not_shot,
+            hunting_cells => hunting_cells,
+        };
+        if candidates == 0 {
+            return None;
+        }
+        let index = self.random.below(candidates.count_ones() as usize);
+        bitboard::positions(candidates).nth(index)
+    }
+
+    /// Adds the cells next to the hit at the `position` to the targets.
+    fn target_around(&mut self, position: Position) {
+        for &adjacent in [
+            position.get_left(),
+            position.get_right(),
+            position.get_above(),
+            position.get_below(),
+        ]
+        .iter()
+        .flatten()
+        {
+            if !self.is_shot(adjacent) {
+                self.targets.push(adjacent);
+            }
+        }
+    }
+}
+
+impl Strategy for HuntStrategy {
+    fn next_shot(&mut self) -> Option<Position> {
+        let position = loop {
+            match self.targets.pop() {
+                Some(position) if self.is_shot(position) => continue,
+                Some(position) => break position,
+                None => break self.hunt()?,
+            }
+        };
+        self.shot |= bitboard::bit(position);
+        Some(position)
+    }
+
+    fn shot_result(&mut self, position: Position, shot_report: &ShotReport) {
+        match shot_report.result {
+            GameBoardShotResult::Hit => {
+                self.hits |= bitboard::bit(position);
+                self.target_around(position);
+            }
+            GameBoardShotResult::Sunk => {
+                // The ship is either revealed or made of the hits connected to the last one
+                let ship = if shot_report.sunk_ship.is_empty() {
+                    bitboard::component(
+                        bitboard::bit(position),
+                        self.hits | bitboard::bit(position),
+                    )
+                } else {
+                    shot_report
+                        .sunk_ship
+                        .iter()
+                        .fold(0, |ship, &cell| ship | bitboard::bit(cell))
+                };
+                self.hits &= !ship;
+                self.shot |= match self.placement {
+                    PlacementRule::NoContact => bitboard::dilate(ship),
+                    PlacementRule::Corners => bitboard::dilate_orthogonally(ship),
+                    PlacementRule::Any => ship,
+                };
+            }
+            GameBoardShotResult::Miss | GameBoardShotResult::Mine => (),
+        }
+    }
+
+    fn revealed_cell(&mut self, position: Position) {
+        if !self.is_shot(position) {
+            self.targets.push(position);
+        }
+    }
+}
+
+/// A built-in strategy with its parameters: `random`, `hunt` or `hunt:<parity>` (e.g. `hunt:2`
+/// to hunt on every second diagonal only).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StrategyKind {
+    Random,
+    Hunt { parity: u8 },
+}
+
+impl StrategyKind {
+    pub fn create(self, rules: &GameRules, random: Random) -> Box<dyn Strategy> {
+        match self {
+            Self::Random => Box::new(RandomStrategy::new(random)),
+            Self::Hunt { parity } => Box::new(HuntStrategy::new(rules, parity, random)),
+        }
+    }
+}
+
+impl std::str::FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            format!(
+                "expected `random`, `hunt` or `hunt:<parity>` with the parity from 1 to {}",
+                GAME_BOARD_SIZE
+            )
+        };
+        match s {
+            "random" => Ok(Self::Random),
+            "hunt" => Ok(Self::Hunt { parity: 1 }),
+            _ if s.starts_with("hunt:") => match s["hunt:".len()..].parse() {
+                Ok(parity) if (1..=GAME_BOARD_SIZE).contains(&parity) => Ok(Self::Hunt { parity }),
+                _ => Err(error()),
+            },
+            _ => Err(error()),
+        }
+    }
+}
+
+impl std::fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Random => f.write_str("random"),
+            Self::Hunt { parity: 1 } => f.write_str("hunt"),
+            Self::Hunt { parity } => write!(f, "hunt:{}", parity),
+        }
+    }
+}
+
+/// A built-in opponent which places its ships and shoots at random cells it has not shot yet.
+/// The same seed gives the same board and the same order of shots.
+pub struct RandomOpponent<B> {
+    pub board: B,
+    pub strategy: RandomStrategy,
+}
+
+impl<B: Board> RandomOpponent<B> {
+    /// Returns `None` if the fleet of the rules cannot be placed on the board.
+    pub fn new(rules: &GameRules, seed: u64) -> Option<Self> {
+        let mut random = Random::new(seed);
+        let board = placement::random_board(rules, &mut random)?;
+        Some(Self {
+            board,
+            strategy: RandomStrategy::new(random),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::bitboard::BitBoard;
+
+    #[test]
+    fn test_RandomOpponent() {
+        let rules = GameRules::default();
+        let shots = |opponent: &mut RandomOpponent<BitBoard>| {
+            std::iter::from_fn(|| opponent.strategy.next_shot()).collect::<Vec<_>>()
+        };
+        let mut opponent = RandomOpponent::<BitBoard>::new(&rules, 7).unwrap();
+        let mut same_opponent = RandomOpponent::<BitBoard>::new(&rules, 7).unwrap();
+        let mut other_opponent = RandomOpponent::<BitBoard>::new(&rules, 8).unwrap();
+        assert_eq!(opponent.board.hits_left(), 20);
+        assert_eq!(opponent.board.to_string(), same_opponent.board.to_string());
+        assert_ne!(opponent.board.to_string(), other_opponent.board.to_string());
+
+        let mut positions = shots(&mut opponent);
+        assert_eq!(positions, shots(&mut same_opponent));
+        assert_ne!(positions, shots(&mut other_opponent));
+        assert_eq!(positions.len(), 100);
+        positions.sort_by_key(|position| position.yx());
+        positions.dedup();
+        assert_eq!(positions.len(), 100);
+    }
+
+    #[test]
+    fn test_StrategyKind_from_str() {
+        for &(s, kind) in &[
+            ("random", StrategyKind::Random),
+            ("hunt", StrategyKind::Hunt { parity: 1 }),
+            ("hunt:3", StrategyKind::Hunt { parity: 3 }),
+        ] {
+            assert_eq!(s.parse::<StrategyKind>().unwrap(), kind);
+            assert_eq!(kind.to_string(), s);
+        }
+        for &s in &["", "hunt:", "hunt:0", "hunt:11", "smart"] {
+            assert!(s.parse::<StrategyKind>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_HuntStrategy() {
+        let rules = GameRules::default();
+        let mut board: BitBoard = placement::random_board(&rules, &mut Random::new(1)).unwrap();
+        let mut strategy = HuntStrategy::new(&rules, 2, Random::new(2));
+
+        // The first shots hunt on every second diagonal only
+        let mut shots = 0;
+        let mut first_hit = None;
+        while first_hit.is_none() {
+            let position = strategy.next_shot().unwrap();
+            assert_eq!((position.x() + position.y()) % 2, 0);
+            let shot_report = ShotReport::from(board.shoot(position));
+            strategy.shot_result(position, &shot_report);
+            if shot_report.result != GameBoardShotResult::Miss {
+                first_hit = Some(position);
+            }
+            shots += 1;
+        }
+
+        // The fleet is sunk without shooting any cell twice or next to a sunk ship
+        while board.hits_left() > 0 {
+            let position = strategy.next_shot().unwrap();
+            assert!(!board.get(position).is_shot(), "{} is shot twice", position);
+            let shot_report = ShotReport::from(board.shoot(position));
+            strategy.shot_result(position, &shot_report);
+            shots += 1;
+        }
+        assert!(shots < 100, "{} shots", shots);
+    }
+}
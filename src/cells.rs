@@ -18,6 +18,7 @@ impl Default for GameBoardCellState {
 pub enum GameBoardCell {
     Empty(GameBoardCellState),
     Ship(GameBoardCellState),
+    Mine(GameBoardCellState),
 }
 
 impl GameBoardCell {
@@ -35,14 +36,29 @@ impl GameBoardCell {
         }
     }
 
+    pub fn is_mine(self) -> bool {
+        match self {
+            Self::Mine(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_shot(self) -> bool {
         match self {
-            Self::Ship(GameBoardCellState::Shot) | Self::Empty(GameBoardCellState::Shot) => true,
+            Self::Ship(GameBoardCellState::Shot)
+            | Self::Empty(GameBoardCellState::Shot)
+            | Self::Mine(GameBoardCellState::Shot) => true,
             _ => false,
         }
     }
 }
 
+/// Checks whether the character can mark the cells of a ship instead of `#`: any Latin letter
+/// except `O` and `X`, which mark shot cells when a board is rendered.
+pub fn is_ship_label(c: char) -> bool {
+    c.is_ascii_alphabetic() && c != 'O' && c != 'X'
+}
+
 impl TryFrom<char> for GameBoardCell {
     type Error = InvalidInputError;
 
@@ -50,6 +66,7 @@ impl TryFrom<char> for GameBoardCell {
         match c {
             '_' => Ok(Self::Empty(GameBoardCellState::NonShot)),
             '#' => Ok(Self::Ship(GameBoardCellState::NonShot)),
+            '*' => Ok(Self::Mine(GameBoardCellState::NonShot)),
             _ => Err(InvalidInputError {}),
         }
     }
@@ -62,6 +79,8 @@ impl From<GameBoardCell> for char {
             GameBoardCell::Empty(GameBoardCellState::Shot) => 'O',
             GameBoardCell::Ship(GameBoardCellState::NonShot) => '#',
             GameBoardCell::Ship(GameBoardCellState::Shot) => '$',
+            GameBoardCell::Mine(GameBoardCellState::NonShot) => '*',
+            GameBoardCell::Mine(GameBoardCellState::Shot) => 'X',
         }
     }
 }
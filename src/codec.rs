@@ -0,0 +1,133 @@
+use bytes::BytesMut;
+use tokio::codec::{Decoder, LinesCodec, LinesCodecError};
+
+/// Lines decoder that limits both the length of a single line and the total amount of bytes
+/// that can be decoded, so a misbehaving player cannot exhaust the judge's memory.
+#[derive(Debug)]
+pub struct BoundedLinesCodec {
+    inner: LinesCodec,
+    bytes_left: usize,
+    is_exhausted: bool,
+}
+
+#[derive(Debug)]
+pub enum BoundedLinesCodecError {
+    MaxLineLengthExceeded,
+    MaxOutputBytesExceeded,
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for BoundedLinesCodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MaxLineLengthExceeded => write!(f, "max line length exceeded"),
+            Self::MaxOutputBytesExceeded => write!(f, "max output bytes exceeded"),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for BoundedLinesCodecError {}
+
+impl From<std::io::Error> for BoundedLinesCodecError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<LinesCodecError> for BoundedLinesCodecError {
+    fn from(err: LinesCodecError) -> Self {
+        match err {
+            LinesCodecError::MaxLineLengthExceeded => Self::MaxLineLengthExceeded,
+            LinesCodecError::Io(err) => Self::Io(err),
+        }
+    }
+}
+
+impl BoundedLinesCodec {
+    pub fn new(max_line_length: usize, max_output_bytes: usize) -> Self {
+        Self {
+            inner: LinesCodec::new_with_max_length(max_line_length),
+            bytes_left: max_output_bytes,
+            is_exhausted: false,
+        }
+    }
+
+    fn decode_with<F>(
+        &mut self,
+        buf: &mut BytesMut,
+        decode: F,
+    ) -> Result<Option<String>, BoundedLinesCodecError>
+    where
+        F: FnOnce(&mut LinesCodec, &mut BytesMut) -> Result<Option<String>, LinesCodecError>,
+    {
+        if self.is_exhausted {
+            buf.clear();
+            return Err(BoundedLinesCodecError::MaxOutputBytesExceeded);
+        }
+        let buf_len = buf.len();
+        let line = decode(&mut self.inner, buf)?;
+        let consumed_len = buf_len - buf.len();
+        if consumed_len > self.bytes_left {
+            self.is_exhausted = true;
+            buf.clear();
+            return Err(BoundedLinesCodecError::MaxOutputBytesExceeded);
+        }
+        self.bytes_left -= consumed_len;
+        Ok(line)
+    }
+}
+
+impl Decoder for BoundedLinesCodec {
+    type Item = String;
+    type Error = BoundedLinesCodecError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<String>, BoundedLinesCodecError> {
+        self.decode_with(buf, LinesCodec::decode)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<String>, BoundedLinesCodecError> {
+        self.decode_with(buf, LinesCodec::decode_eof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_BoundedLinesCodec_decode() {
+        let mut codec = BoundedLinesCodec::new(10, 100);
+        let mut buf = BytesMut::from(&b"1 1\n10 10\r\n1"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some("1 1".to_owned()));
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some("10 10".to_owned()));
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), Some("1".to_owned()));
+    }
+
+    #[test]
+    fn test_BoundedLinesCodec_max_line_length() {
+        let mut codec = BoundedLinesCodec::new(10, 100);
+        let mut buf = BytesMut::from(&b"__________#\n"[..]);
+        match codec.decode(&mut buf) {
+            Err(BoundedLinesCodecError::MaxLineLengthExceeded) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_BoundedLinesCodec_max_output_bytes() {
+        let mut codec = BoundedLinesCodec::new(10, 8);
+        let mut buf = BytesMut::from(&b"1 1\n2 2\n3 3\n"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some("1 1".to_owned()));
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some("2 2".to_owned()));
+        match codec.decode(&mut buf) {
+            Err(BoundedLinesCodecError::MaxOutputBytesExceeded) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        buf.extend_from_slice(b"4 4\n");
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
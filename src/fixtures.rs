@@ -0,0 +1,30 @@
+use structopt::StructOpt as _;
+
+use crate::board::GameBoard;
+use crate::rules::GameRules;
+
+/// A board with the classic fleet: the ships are placed in the top left corner row by row from
+/// the largest one.
+pub const BOARD: &str = "\
+                         ####______\n__________\n###_###___\n__________\n##_##_##__\n\
+                         __________\n#_#_#_#___\n__________\n__________\n__________";
+
+/// The classic fleet placed from the smallest ship, with the largest one in the 7th row.
+pub const OTHER_BOARD: &str = "\
+                               #_#_#_#___\n__________\n##_##_##__\n__________\n###_###___\n\
+                               __________\n####______\n__________\n__________\n__________";
+
+pub fn board() -> GameBoard {
+    GameBoard::from_lines(BOARD.split('\n')).unwrap()
+}
+
+/// The beginning of the log of a game of the player with `BOARD` against the one with
+/// `OTHER_BOARD`, to append the shots to.
+pub fn log_boards() -> String {
+    format!("{}\n\n{}\n\n", BOARD, OTHER_BOARD)
+}
+
+/// The rules set by the command line arguments of the judge.
+pub fn rules(args: &[&str]) -> GameRules {
+    GameRules::from_iter(std::iter::once(&"judge").chain(args))
+}
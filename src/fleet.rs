@@ -0,0 +1,309 @@
+use crate::position::Position;
+use crate::GAME_BOARD_SIZE;
+
+/// Shape of a ship (a polyomino) as a list of `(x, y)` offsets of its cells from the top-left
+/// corner of its bounding box ordered row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShipShape {
+    cells: Vec<(u8, u8)>,
+}
+
+impl ShipShape {
+    fn normalized(cells: &[(i16, i16)]) -> Self {
+        let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let mut cells: Vec<(u8, u8)> = cells
+            .iter()
+            .map(|&(x, y)| ((x - min_x) as u8, (y - min_y) as u8))
+            .collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        Self { cells }
+    }
+
+    pub fn from_positions(positions: &[Position]) -> Self {
+        let cells: Vec<(i16, i16)> = positions
+            .iter()
+            .map(|position| (i16::from(position.x()), i16::from(position.y())))
+            .collect();
+        Self::normalized(&cells)
+    }
+
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns positions of the ship cells if the top-left corner of the shape bounding box is
+    /// placed at the given position, or `None` if the ship does not fit the board.
+    pub fn place_at(&self, top_left: Position) -> Option<Vec<Position>> {
+        self.cells
+            .iter()
+            .map(|&(x, y)| Position::from_xy(top_left.x() + x, top_left.y() + y))
+            .collect()
+    }
+
+    /// Returns the shape itself and all its distinct rotations and reflections.
+    pub fn variants(&self) -> Vec<Self> {
+        let mut cells: Vec<(i16, i16)> = self
+            .cells
+            .iter()
+            .map(|&(x, y)| (i16::from(x), i16::from(y)))
+            .collect();
+        let mut variants: Vec<Self> = Vec::with_capacity(8);
+        for _ in 0..2 {
+            for _ in 0..4 {
+                let variant = Self::normalized(&cells);
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+                // Rotate by 90 degrees
+                for cell in &mut cells {
+                    *cell = (-cell.1, cell.0);
+                }
+            }
+            // Reflect horizontally
+            for cell in &mut cells {
+                cell.0 = -cell.0;
+            }
+        }
+        variants
+    }
+
+    /// Checks whether the shape matches the other one up to rotations and reflections.
+    pub fn is_congruent(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.variants().contains(other)
+    }
+
+    fn is_connected(&self) -> bool {
+        let mut connected = vec![self.cells[0]];
+        let mut next_cell_index = 0;
+        while let Some(&(x, y)) = connected.get(next_cell_index) {
+            for &cell in &self.cells {
+                let is_adjacent = (cell.0 == x && (cell.1 + 1 == y || cell.1 == y + 1))
+                    || (cell.1 == y && (cell.0 + 1 == x || cell.0 == x + 1));
+                if is_adjacent && !connected.contains(&cell) {
+                    connected.push(cell);
+                }
+            }
+            next_cell_index += 1;
+        }
+        connected.len() == self.cells.len()
+    }
+}
+
+/// Parses a shape drawn row by row with rows separated by `/`, where `#` is a cell of the ship
+/// and `_` is an empty cell, e.g. `#_/#_/##` is an L-shaped ship.
+impl std::str::FromStr for ShipShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cells = Vec::new();
+        for (y, row) in s.split('/').enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => cells.push((x as i16, y as i16)),
+                    '_' => (),
+                    _ => return Err(format!("unexpected `{}` in ship shape `{}`", c, s)),
+                }
+            }
+        }
+        if cells.is_empty() {
+            return Err(format!("ship shape `{}` has no cells", s));
+        }
+        let shape = Self::normalized(&cells);
+        if !shape.is_connected() {
+            return Err(format!("ship shape `{}` is not connected", s));
+        }
+        Ok(shape)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FleetEntry {
+    pub shape: ShipShape,
+    pub count: u8,
+}
+
+/// Ships each player has to place on their board. Ships can be placed rotated or reflected.
+#[derive(Debug, Clone)]
+pub struct Fleet {
+    entries: Vec<FleetEntry>,
+}
+
+impl Fleet {
+    /// Creates a fleet that consists of the ships of the given shapes, e.g. of some of the ships
+    /// of a parsed fleet, so there are no more than 255 ships of any shape.
+    pub fn from_shapes<I>(shapes: I) -> Self
+    where
+        I: IntoIterator<Item = ShipShape>,
+    {
+        let mut fleet = Self {
+            entries: Vec::new(),
+        };
+        for shape in shapes {
+            fleet
+                .add(shape, 1)
+                .expect("no more than 255 ships of a shape");
+        }
+        fleet
+    }
+
+    fn add(&mut self, shape: ShipShape, count: u8) -> Result<(), String> {
+        // Congruent shapes are indistinguishable on the board
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.shape.is_congruent(&shape))
+        {
+            Some(entry) => {
+                entry.count = entry
+                    .count
+                    .checked_add(count)
+                    .ok_or("too many ships of the same shape")?;
+            }
+            None => self.entries.push(FleetEntry { shape, count }),
+        }
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[FleetEntry] {
+        &self.entries
+    }
+
+    /// Returns the shapes of all the ships of the fleet, one per ship.
+    pub fn ships(&self) -> Vec<ShipShape> {
+        self.entries
+            .iter()
+            .flat_map(|entry| (0..entry.count).map(move |_| entry.shape.clone()))
+            .collect()
+    }
+
+    /// Returns the index of the fleet entry the ship of the given shape belongs to.
+    pub fn find(&self, shape: &ShipShape) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.shape.is_congruent(shape))
+    }
+}
+
+/// Parses a comma-separated list of ships in the `<count>x<shape>` form (the count can be
+/// omitted for a single ship), e.g. the classic fleet is `1x####,2x###,3x##,4x#`.
+impl std::str::FromStr for Fleet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fleet = Self {
+            entries: Vec::new(),
+        };
+        for entry in s.split(',') {
+            let (count, shape) = match entry.find('x') {
+                Some(separator_index) => (
+                    entry[..separator_index]
+                        .parse()
+                        .map_err(|err| format!("invalid ships count in `{}`: {}", entry, err))?,
+                    &entry[separator_index + 1..],
+                ),
+                None => (1, entry),
+            };
+            fleet.add(shape.parse()?, count)?;
+        }
+        let cells_count: u32 = fleet
+            .entries
+            .iter()
+            .map(|entry| u32::from(entry.count) * entry.shape.size() as u32)
+            .sum();
+        if cells_count == 0 {
+            return Err("the fleet has no ships".to_owned());
+        }
+        if cells_count > u32::from(GAME_BOARD_SIZE) * u32::from(GAME_BOARD_SIZE) {
+            return Err("the fleet does not fit the board".to_owned());
+        }
+        Ok(fleet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_ShipShape_from_str() {
+        let shape: ShipShape = "#_/#_/##".parse().unwrap();
+        assert_eq!(shape.cells, vec![(0, 0), (0, 1), (0, 2), (1, 2)]);
+        assert_eq!(
+            "_#/_#".parse::<ShipShape>().unwrap(),
+            "#/#".parse().unwrap()
+        );
+        assert!("".parse::<ShipShape>().is_err());
+        assert!("__".parse::<ShipShape>().is_err());
+        assert!("#_#".parse::<ShipShape>().is_err());
+        assert!("#/_#".parse::<ShipShape>().is_err());
+        assert!("#x".parse::<ShipShape>().is_err());
+    }
+
+    #[test]
+    fn test_ShipShape_variants() {
+        let count_variants = |s: &str| s.parse::<ShipShape>().unwrap().variants().len();
+        assert_eq!(count_variants("#"), 1);
+        assert_eq!(count_variants("##/##"), 1);
+        assert_eq!(count_variants("####"), 2);
+        assert_eq!(count_variants("###/_#_"), 4);
+        assert_eq!(count_variants("#_/#_/##"), 8);
+
+        let l_shape: ShipShape = "#_/#_/##".parse().unwrap();
+        assert!(l_shape.is_congruent(&"###/#__".parse().unwrap()));
+        assert!(l_shape.is_congruent(&"_#/_#/##".parse().unwrap()));
+        assert!(!l_shape.is_congruent(&"##/##".parse().unwrap()));
+        assert!(!l_shape.is_congruent(&"##_/_##".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_Fleet_from_str() {
+        let fleet: Fleet = "1x####,2x###,3x##,4x#".parse().unwrap();
+        let counts: Vec<(usize, u8)> = fleet
+            .entries()
+            .iter()
+            .map(|entry| (entry.shape.size(), entry.count))
+            .collect();
+        assert_eq!(counts, vec![(4, 1), (3, 2), (2, 3), (1, 4)]);
+
+        let fleet: Fleet = "##/##,2x#_/##,#/#/#,_#/##".parse().unwrap();
+        assert_eq!(fleet.entries().len(), 3);
+        assert_eq!(fleet.entries()[1].count, 3);
+        assert_eq!(fleet.find(&"##_/_##".parse().unwrap()), None);
+        assert_eq!(fleet.find(&"###".parse().unwrap()), Some(2));
+
+        assert!("2x".parse::<Fleet>().is_err());
+        assert!("ax#".parse::<Fleet>().is_err());
+        assert!("0x#".parse::<Fleet>().is_err());
+        assert!("101x#".parse::<Fleet>().is_err());
+        assert!("255x#,1x#".parse::<Fleet>().is_err());
+        assert!("200x#,#/#,100x#".parse::<Fleet>().is_err());
+    }
+
+    #[test]
+    fn test_Fleet_from_shapes() {
+        let fleet = Fleet::from_shapes(vec![
+            "##".parse().unwrap(),
+            "#".parse().unwrap(),
+            "#/#".parse().unwrap(),
+        ]);
+        let counts: Vec<(usize, u8)> = fleet
+            .entries()
+            .iter()
+            .map(|entry| (entry.shape.size(), entry.count))
+            .collect();
+        assert_eq!(counts, vec![(2, 2), (1, 1)]);
+        assert_eq!(fleet.ships().len(), 3);
+    }
+
+    #[test]
+    fn test_ShipShape_place_at() {
+        let shape: ShipShape = "#_/##".parse().unwrap();
+        let cells = shape.place_at("9 1".parse().unwrap()).unwrap();
+        assert_eq!(cells, Position::parse_list("9 1 9 2 10 2").unwrap());
+        assert_eq!(shape.place_at("10 1".parse().unwrap()), None);
+        assert_eq!(shape.place_at("1 10".parse().unwrap()), None);
+    }
+}
@@ -0,0 +1,466 @@
+use crate::board::{Board, GameBoardShotResult, ShotReport};
+use crate::position::Position;
+use crate::random::Random;
+use crate::rules::{GameRules, RuleViolation, TurnPolicy};
+use crate::GameResult;
+
+/// Whose turn it is (players are indexed from 0) and how many shots they make.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Turn {
+    /// The player makes a single shot and learns its result before the next one.
+    Shot { player: usize },
+    /// The player fires the rest of a volley of `size` shots at once.
+    Volley { player: usize, size: u8 },
+}
+
+impl Turn {
+    pub fn player(self) -> usize {
+        match self {
+            Self::Shot { player } | Self::Volley { player, .. } => player,
+        }
+    }
+}
+
+/// A game played by the rules, without any I/O: a driver (the judge playing the bots, the
+/// interactive mode, the simulators) asks whose turn it is, makes the shots of that player and
+/// tells the players their results, until the game is over.
+pub struct Game<'r, B> {
+    rules: &'r GameRules,
+    /// The boards of the players, indexed by the player.
+    boards: [B; 2],
+    shots: [u16; 2],
+    skips_turn: [bool; 2],
+    /// The player whose turn it is (or who made the last move once the game is over).
+    player: usize,
+    /// The shots left in the volley being fired.
+    volley_left: u8,
+    /// Whether any shot of the volley being fired makes the player skip their next turn.
+    volley_skips_turn: bool,
+    result: Option<GameResult>,
+    adjudicated: bool,
+    /// Picks the ship cells revealed as a penalty for hitting mines.
+    random: Random,
+}
+
+impl<'r, B: Board> Game<'r, B> {
+    /// Starts the game of the players with the boards; player 1 (index 0) goes first. The
+    /// `random` generator makes the random choices of the rules, so a seeded one replays the game.
+    pub fn new(rules: &'r GameRules, boards: [B; 2], random: Random) -> Self {
+        let mut game = Self {
+            rules,
+            boards,
+            shots: [0; 2],
+            skips_turn: [false; 2],
+            player: 0,
+            volley_left: 0,
+            volley_skips_turn: false,
+            result: None,
+            adjudicated: false,
+            random,
+        };
+        game.settle();
+        game
+    }
+
+    pub fn rules(&self) -> &'r GameRules {
+        self.rules
+    }
+
+    /// Returns `None` once the game is over.
+    pub fn turn(&self) -> Option<Turn> {
+        if self.is_over() {
+            return None;
+        }
+        Some(match self.rules.turns {
+            TurnPolicy::Classic | TurnPolicy::Alternate => Turn::Shot {
+                player: self.player,
+            },
+            TurnPolicy::Salvo => Turn::Volley {
+                player: self.player,
+                size: self.volley_left,
+            },
+        })
+    }
+
+    /// Makes the next shot of the player whose turn it is at the `position` of the opponent's
+    /// board. A shot that violates the rules loses the game. Panics if the game is over, as the
+    /// counters of the shots would go wrong.
+    pub fn shoot(&mut self, position: Position) -> Result<ShotReport, RuleViolation> {
+        assert!(!self.is_over(), "the game is over");
+        let player = self.player;
+        let opponent = 1 - player;
+        self.shots[player] += 1;
+        let (first, second) = self.boards.split_at_mut(1);
+        let (own_board, opponent_board) = if player == 0 {
+            (&mut first[0], &mut second[0])
+        } else {
+            (&mut second[0], &mut first[0])
+        };
+        let shot_report = match self.rules.apply_shot(
+            self.shots[player],
+            own_board,
+            opponent_board,
+            position,
+            &mut self.random,
+        ) {
+            Ok(shot_report) => shot_report,
+            Err(violation) => {
+                self.result = Some(win(opponent));
+                return Err(violation);
+            }
+        };
+
+        let skips_next_turn = self.rules.skips_next_turn(shot_report.result);
+        match self.rules.turns {
+            // A player keeps shooting until they miss
+            TurnPolicy::Classic => match shot_report.result {
+                GameBoardShotResult::Miss | GameBoardShotResult::Mine => {
+                    self.skips_turn[player] = skips_next_turn;
+                    self.pass_turn();
+                }
+                GameBoardShotResult::Hit | GameBoardShotResult::Sunk => {
+                    if self.boards[opponent].hits_left() == 0 {
+                        self.result = Some(win(player));
+                    } else {
+                        self.settle();
+                    }
+                }
+            },
+            TurnPolicy::Alternate => {
+                self.skips_turn[player] = skips_next_turn;
+                self.pass_turn();
+            }
+            TurnPolicy::Salvo => {
+                self.volley_skips_turn |= skips_next_turn;
+                self.volley_left -= 1;
+                if self.volley_left == 0 {
+                    if self.boards[opponent].hits_left() == 0 {
+                        self.result = Some(win(player));
+                    } else {
+                        self.skips_turn[player] = self.volley_skips_turn;
+                        self.pass_turn();
+                    }
+                }
+            }
+        }
+        Ok(shot_report)
+    }
+
+    /// The player whose turn it is failed to shoot, so the opponent wins. Panics if the game is
+    /// over.
+    pub fn forfeit(&mut self) {
+        assert!(!self.is_over(), "the game is over");
+        self.result = Some(win(1 - self.player));
+    }
+
+    /// Returns `None` until the game is over.
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// The index of the player who won the game; `None` until the game is over or if it is a draw.
+    pub fn winner(&self) -> Option<usize> {
+        match self.result? {
+            GameResult::Player1Win => Some(0),
+            GameResult::Player2Win => Some(1),
+            GameResult::Draw => None,
+        }
+    }
+
+    /// Checks whether the game was decided by the rules once both players made the maximum
+    /// number of shots.
+    pub fn is_adjudicated(&self) -> bool {
+        self.adjudicated
+    }
+
+    pub fn board(&self, player: usize) -> &B {
+        &self.boards[player]
+    }
+
+    /// The number of shots each player made.
+    pub fn shots(&self) -> [u16; 2] {
+        self.shots
+    }
+
+    /// The number of hits the player still needs to sink the opponent's fleet.
+    pub fn hits_left(&self, player: usize) -> u8 {
+        self.boards[1 - player].hits_left()
+    }
+
+    /// Passes the turn on until a player who can shoot is found or the game is over.
+    fn settle(&mut self) {
+        let max_shots = self.rules.max_shots;
+        loop {
+            let player = self.player;
+            match self.rules.turns {
+                TurnPolicy::Classic => {
+                    if self.shots[player] == max_shots {
+                        if self.shots[1 - player] == max_shots {
+                            return self.adjudicate();
+                        }
+                    } else if !std::mem::replace(&mut self.skips_turn[player], false) {
+                        return;
+                    }
+                }
+                TurnPolicy::Alternate => {
+                    if player == 0 && self.shots == [max_shots; 2] {
+                        return self.adjudicate();
+                    }
+                    if self.shots[player] < max_shots
+                        && !std::mem::replace(&mut self.skips_turn[player], false)
+                    {
+                        return;
+                    }
+                }
+                TurnPolicy::Salvo => {
+                    if player == 0 && self.shots == [max_shots; 2] {
+                        return self.adjudicate();
+                    }
+                    // A volley has as many shots as many ships the player has afloat, but no
+                    // more than the player has left until the shots limit
+                    let volley_size = std::cmp::min(
+                        max_shots - self.shots[player],
+                        u16::from(self.boards[player].ships_left()),
+                    ) as u8;
+                    if !std::mem::replace(&mut self.skips_turn[player], false) && volley_size > 0 {
+                        self.volley_left = volley_size;
+                        self.volley_skips_turn = false;
+                        return;
+                    }
+                }
+            }
+            if !self.next_player() {
+                return;
+            }
+        }
+    }
+
+    fn pass_turn(&mut self) {
+        if self.next_player() {
+            self.settle();
+        }
+    }
+
+    /// Gives the turn to the other player. With alternate turns, both players make the same
+    /// number of shots, so the game is decided once the round of player 2 is over. Returns
+    /// `false` if the game is over.
+    fn next_player(&mut self) -> bool {
+        if self.rules.turns == TurnPolicy::Alternate && self.player == 1 {
+            self.result = match (self.hits_left(0), self.hits_left(1)) {
+                (0, 0) => Some(GameResult::Draw),
+                (0, _) => Some(GameResult::Player1Win),
+                (_, 0) => Some(GameResult::Player2Win),
+                _ => None,
+            };
+            if self.is_over() {
+                return false;
+            }
+        }
+        self.player = 1 - self.player;
+        true
+    }
+
+    fn adjudicate(&mut self) {
+        self.result = Some(self.rules.adjudicate(self.hits_left(0), self.hits_left(1)));
+        self.adjudicated = true;
+    }
+}
+
+/// The result of the game won by the player with the index.
+fn win(player: usize) -> GameResult {
+    if player == 0 {
+        GameResult::Player1Win
+    } else {
+        GameResult::Player2Win
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::board::GameBoard;
+    use crate::fixtures;
+
+    fn rules(args: &[&str]) -> GameRules {
+        fixtures::rules(&[&["--fleet", "1x##,1x#"], args].concat())
+    }
+
+    /// Both players place a ship of two cells at `1 1`-`2 1` and a single-cell one at `1 3`,
+    /// and a mine at `10 10` if the rules have one.
+    fn game(rules: &GameRules) -> Game<'_, GameBoard> {
+        let mine = if rules.mines == 0 { '_' } else { '*' };
+        let lines = format!(
+            "\
+             ##________ \
+             __________ \
+             #_________ \
+             __________ \
+             __________ \
+             __________ \
+             __________ \
+             __________ \
+             __________ \
+             _________{} \
+             ",
+            mine
+        );
+        let board = GameBoard::from_lines_with_rules(lines.split_whitespace(), rules).unwrap();
+        Game::new(rules, [board.clone(), board], Random::new(0))
+    }
+
+    fn at(x: u8, y: u8) -> Position {
+        Position::from_xy(x - 1, y - 1).unwrap()
+    }
+
+    fn shoot(game: &mut Game<'_, GameBoard>, x: u8, y: u8) -> GameBoardShotResult {
+        game.shoot(at(x, y)).unwrap().result
+    }
+
+    #[test]
+    fn test_Game_classic() {
+        let rules = rules(&[]);
+        let mut game = game(&rules);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 0 }));
+        assert_eq!(shoot(&mut game, 1, 1), GameBoardShotResult::Hit);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 0 }));
+        assert_eq!(shoot(&mut game, 5, 5), GameBoardShotResult::Miss);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+        assert_eq!(shoot(&mut game, 5, 5), GameBoardShotResult::Miss);
+        assert_eq!(shoot(&mut game, 2, 1), GameBoardShotResult::Sunk);
+        assert_eq!(shoot(&mut game, 1, 3), GameBoardShotResult::Sunk);
+        assert!(game.is_over());
+        assert_eq!(game.turn(), None);
+        assert_eq!(game.result(), Some(GameResult::Player1Win));
+        assert_eq!(game.winner(), Some(0));
+        assert!(!game.is_adjudicated());
+        assert_eq!(game.shots(), [4, 1]);
+        assert_eq!((game.hits_left(0), game.hits_left(1)), (0, 3));
+    }
+
+    #[test]
+    fn test_Game_alternate() {
+        let rules = rules(&["--turns", "alternate"]);
+        let mut game = game(&rules);
+        for &(x, y) in &[(1, 1), (2, 1)] {
+            assert_eq!(game.turn(), Some(Turn::Shot { player: 0 }));
+            assert_ne!(shoot(&mut game, x, y), GameBoardShotResult::Miss);
+            assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+            assert_ne!(shoot(&mut game, x, y), GameBoardShotResult::Miss);
+        }
+        // Player 2 makes the last shot of the round even though player 1 has sunk the fleet
+        assert_eq!(shoot(&mut game, 1, 3), GameBoardShotResult::Sunk);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+        assert!(!game.is_over());
+        assert_eq!(shoot(&mut game, 1, 3), GameBoardShotResult::Sunk);
+        assert_eq!(game.result(), Some(GameResult::Draw));
+        assert_eq!(game.winner(), None);
+
+        let mut game = self::game(&rules);
+        shoot(&mut game, 1, 3);
+        shoot(&mut game, 5, 5);
+        shoot(&mut game, 1, 1);
+        shoot(&mut game, 5, 5);
+        shoot(&mut game, 2, 1);
+        assert!(!game.is_over());
+        shoot(&mut game, 5, 5);
+        assert_eq!(game.result(), Some(GameResult::Player1Win));
+    }
+
+    #[test]
+    fn test_Game_salvo() {
+        let rules = rules(&["--turns", "salvo", "--mines", "1"]);
+        let mut game = game(&rules);
+        assert_eq!(game.turn(), Some(Turn::Volley { player: 0, size: 2 }));
+        assert_eq!(shoot(&mut game, 10, 10), GameBoardShotResult::Mine);
+        assert_eq!(game.turn(), Some(Turn::Volley { player: 0, size: 1 }));
+        assert_eq!(shoot(&mut game, 1, 3), GameBoardShotResult::Sunk);
+
+        // Player 2 has a ship less afloat, and player 1 skips the turn for hitting the mine
+        assert_eq!(game.turn(), Some(Turn::Volley { player: 1, size: 1 }));
+        assert_eq!(shoot(&mut game, 5, 5), GameBoardShotResult::Miss);
+        assert_eq!(game.turn(), Some(Turn::Volley { player: 1, size: 1 }));
+        assert_eq!(shoot(&mut game, 1, 1), GameBoardShotResult::Hit);
+        assert_eq!(game.turn(), Some(Turn::Volley { player: 0, size: 2 }));
+        assert_eq!(shoot(&mut game, 1, 1), GameBoardShotResult::Hit);
+        assert!(!game.is_over());
+        assert_eq!(shoot(&mut game, 2, 1), GameBoardShotResult::Sunk);
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(game.shots(), [4, 2]);
+    }
+
+    #[test]
+    fn test_Game_mine_penalty() {
+        let rules = rules(&["--mines", "1"]);
+        let mut game = game(&rules);
+        assert_eq!(shoot(&mut game, 10, 10), GameBoardShotResult::Mine);
+        assert_eq!(shoot(&mut game, 5, 5), GameBoardShotResult::Miss);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+        assert_eq!(shoot(&mut game, 5, 6), GameBoardShotResult::Miss);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 0 }));
+
+        let rules = self::rules(&["--mines", "1", "--mine-penalty", "reveal"]);
+        let mut game = self::game(&rules);
+        let shot_report = game.shoot(at(10, 10)).unwrap();
+        assert!(shot_report.revealed_cell.is_some());
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+        assert_eq!(shoot(&mut game, 5, 5), GameBoardShotResult::Miss);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 0 }));
+    }
+
+    #[test]
+    fn test_Game_max_shots() {
+        let rules = rules(&["--max-shots", "2"]);
+        let mut game = game(&rules);
+        shoot(&mut game, 1, 1);
+        shoot(&mut game, 2, 1);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+        shoot(&mut game, 1, 1);
+        assert_eq!(game.turn(), Some(Turn::Shot { player: 1 }));
+        shoot(&mut game, 5, 5);
+        assert!(game.is_adjudicated());
+        assert_eq!(game.result(), Some(GameResult::Player1Win));
+
+        let rules = self::rules(&["--max-shots", "2", "--adjudication", "draw"]);
+        let mut game = self::game(&rules);
+        for _ in 0..4 {
+            shoot(&mut game, 5, 5);
+        }
+        assert_eq!(game.shots(), [2, 2]);
+        assert_eq!(game.result(), Some(GameResult::Draw));
+    }
+
+    #[test]
+    fn test_Game_forfeit() {
+        let rules = rules(&["--repeat-shots", "forfeit"]);
+        let mut game = game(&rules);
+        shoot(&mut game, 5, 5);
+        shoot(&mut game, 5, 5);
+        assert_eq!(shoot(&mut game, 6, 6), GameBoardShotResult::Miss);
+        assert_eq!(
+            game.shoot(at(5, 5)).unwrap_err(),
+            RuleViolation::RepeatedShot
+        );
+        assert_eq!(game.result(), Some(GameResult::Player1Win));
+        assert!(!game.is_adjudicated());
+
+        let mut game = self::game(&rules);
+        game.forfeit();
+        assert_eq!(game.turn(), None);
+        assert_eq!(game.winner(), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "the game is over")]
+    fn test_Game_shoot_after_game_over() {
+        let rules = rules(&["--turns", "salvo"]);
+        let mut game = game(&rules);
+        game.forfeit();
+        game.shoot(at(5, 5)).ok();
+    }
+}
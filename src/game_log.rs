@@ -0,0 +1,346 @@
+use crate::board::{GameBoard, GameBoardShotResult, ShotReport};
+use crate::game::Game;
+use crate::position::Position;
+use crate::rules::{GameRules, RuleViolation};
+use crate::{GameResult, GAME_BOARD_SIZE};
+
+/// A shot as it is written into the game log, e.g. `1 5 3 hit`, `2 1 1 miss repeat`,
+/// `1 3 1 sunk -- 3-cell horizontal ship at 1 1` or `1 1 1 repeat` if the shot violated the rules.
+#[derive(Debug, Clone)]
+pub struct LoggedShot {
+    pub player_number: u8,
+    pub position: Position,
+    pub outcome: Result<ShotReport, RuleViolation>,
+    /// The cell was already shot before (the shot still counts as a miss).
+    pub is_repeated: bool,
+    /// The name of the ship the shot sunk, e.g. `3-cell horizontal ship at 1 1` (logs written
+    /// before the ships were named have none).
+    pub sunk_ship_name: Option<String>,
+}
+
+impl LoggedShot {
+    fn parse(line: &str) -> Option<Self> {
+        let (line, sunk_ship_name) = match line.find(" -- ") {
+            Some(index) => (&line[..index], Some(line[index + 4..].to_owned())),
+            None => (line, None),
+        };
+        let mut tokens = line.splitn(4, ' ');
+        let player_number = match tokens.next()? {
+            "1" => 1,
+            "2" => 2,
+            _ => return None,
+        };
+        let position = Position::from_coords(tokens.next()?, tokens.next()?).ok()?;
+        let mut words: Vec<&str> = tokens.next()?.split(' ').collect();
+        let is_repeated = words.len() > 1 && words.last() == Some(&"repeat");
+        if is_repeated {
+            words.pop();
+        }
+        let outcome = words.join(" ");
+        let outcome = match outcome.parse::<ShotReport>() {
+            Ok(shot_report) => Ok(shot_report),
+            Err(_) if !is_repeated => Err(outcome.parse::<RuleViolation>().ok()?),
+            Err(_) => return None,
+        };
+        Some(Self {
+            player_number,
+            position,
+            outcome,
+            is_repeated,
+            sunk_ship_name,
+        })
+    }
+
+    /// Writes the shot as a line of the game log, the way `parse` reads it back.
+    fn write_line<W: std::fmt::Write>(&self, log: &mut W) -> std::fmt::Result {
+        write!(log, "{} {} ", self.player_number, self.position)?;
+        match self.outcome {
+            Ok(ref shot_report) if self.is_repeated => write!(log, "{} repeat", shot_report)?,
+            Ok(ref shot_report) => write!(log, "{}", shot_report)?,
+            Err(violation) => write!(log, "{}", violation.as_str())?,
+        }
+        if let Some(ref sunk_ship_name) = self.sunk_ship_name {
+            write!(log, " -- {}", sunk_ship_name)?;
+        }
+        writeln!(log)
+    }
+}
+
+impl std::fmt::Display for LoggedShot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Player {} shoots {}: ",
+            self.player_number, self.position
+        )?;
+        match self.outcome {
+            Ok(ref shot_report) if self.is_repeated => write!(f, "{} (repeated)", shot_report),
+            Ok(ref shot_report) => write!(f, "{}", shot_report),
+            Err(violation) => write!(f, "{} (violates the rules)", violation.as_str()),
+        }?;
+        match self.sunk_ship_name {
+            Some(ref sunk_ship_name) => write!(f, " -- {}", sunk_ship_name),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Makes the next shot of the player whose turn it is and writes it into the log the way the judge
+/// prints it: repeated shots are marked with `repeat`, shots that violate the rules are written
+/// with the violation instead of the shot result, and shots that sink a ship name the ship.
+pub fn log_shot<W: std::fmt::Write>(
+    log: &mut W,
+    game: &mut Game<'_, GameBoard>,
+    position: Position,
+) -> LoggedShot {
+    let player = game.turn().expect("the game is over").player();
+    let is_repeated = game.board(1 - player).get(position).is_shot();
+    let outcome = game.shoot(position);
+    let sunk_ship_name = match outcome {
+        Ok(ref shot_report) if shot_report.result == GameBoardShotResult::Sunk => game
+            .board(1 - player)
+            .ship_at(position)
+            .map(|ship| ship.to_string()),
+        _ => None,
+    };
+    let shot = LoggedShot {
+        player_number: player as u8 + 1,
+        position,
+        is_repeated: is_repeated && outcome.is_ok(),
+        outcome,
+        sunk_ship_name,
+    };
+    let _ = shot.write_line(log);
+    shot
+}
+
+/// Writes why the game was adjudicated into the log the way the judge prints it.
+pub fn log_adjudication<W: std::fmt::Write>(log: &mut W, game: &Game<'_, GameBoard>) {
+    let _ = writeln!(
+        log,
+        "Shots limit of {} per player is reached (player 1 has {} hits left, player 2 has {} hits left)",
+        game.rules().max_shots,
+        game.hits_left(0),
+        game.hits_left(1)
+    );
+}
+
+/// A game as it is printed by the judge: the boards of both players, the shots and the verdict.
+#[derive(Debug, Clone)]
+pub struct GameLog {
+    /// The boards of the first and the second player as they were placed, unless some of the
+    /// players failed to place their ships.
+    pub boards: Option<[GameBoard; 2]>,
+    pub shots: Vec<LoggedShot>,
+    /// Other messages of the judge, e.g. why the game was adjudicated.
+    pub notes: Vec<String>,
+    /// The verdict, unless the log is truncated.
+    pub result: Option<GameResult>,
+}
+
+impl GameLog {
+    /// Parses the log of a game played by the `rules` (the boards are validated by the rules).
+    pub fn parse(log: &str, rules: &GameRules) -> Result<Self, String> {
+        let mut lines = log
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .peekable();
+
+        let mut board_lines = Vec::new();
+        while let Some(line) = lines.peek() {
+            if line.len() != usize::from(GAME_BOARD_SIZE) || line.contains(' ') {
+                break;
+            }
+            board_lines.push(*line);
+            lines.next();
+        }
+        let boards = match board_lines.len() {
+            0 => None,
+            20 => {
+                let parse_board = |player_number, lines: &[&str]| {
+                    GameBoard::from_lines_with_rules(lines.iter(), rules).map_err(|_| {
+                        format!(
+                            "the board of player {} is invalid under the given rules",
+                            player_number
+                        )
+                    })
+                };
+                Some([
+                    parse_board(1, &board_lines[..10])?,
+                    parse_board(2, &board_lines[10..])?,
+                ])
+            }
+            _ => return Err("the boards of the players are incomplete".to_owned()),
+        };
+
+        let mut game_log = Self {
+            boards,
+            shots: Vec::new(),
+            notes: Vec::new(),
+            result: None,
+        };
+        for line in lines {
+            if let Some(game_result) = GameResult::from_verdict(line) {
+                game_log.result = Some(game_result);
+            } else if let Some(shot) = LoggedShot::parse(line) {
+                game_log.shots.push(shot);
+            } else {
+                game_log.notes.push(line.to_owned());
+            }
+        }
+        if !game_log.shots.is_empty() && game_log.boards.is_none() {
+            return Err("the log has shots but no boards".to_owned());
+        }
+        Ok(game_log)
+    }
+
+    /// Replays the game and returns the boards of both players after every shot, starting with
+    /// the boards as they were placed.
+    pub fn replay(&self) -> Vec<[GameBoard; 2]> {
+        let mut boards = match self.boards {
+            Some(ref boards) => boards.clone(),
+            None => return Vec::new(),
+        };
+        let mut states = Vec::with_capacity(self.shots.len() + 1);
+        states.push(boards.clone());
+        for shot in &self.shots {
+            if let Ok(ref shot_report) = shot.outcome {
+                let (player_index, opponent_index) = if shot.player_number == 1 {
+                    (0, 1)
+                } else {
+                    (1, 0)
+                };
+                boards[opponent_index].shoot(shot.position);
+                if !shot_report.sunk_ship.is_empty() {
+                    boards[opponent_index].reveal_around_ship(shot.position);
+                }
+                if let Some(revealed_cell) = shot_report.revealed_cell {
+                    boards[player_index].mark_revealed(revealed_cell);
+                }
+            }
+            states.push(boards.clone());
+        }
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::random::Random;
+
+    const LOG: &str = "\
+####______
+__________
+###_###___
+__________
+##_##_##__
+__________
+#_#_#_#___
+__________
+__________
+__________
+
+_________#
+_________#
+_________#
+_________#
+__________
+###_###___
+__________
+##_##_##__
+__________
+#_#_#_#___
+
+1 10 1 hit
+1 10 2 hit
+1 9 2 miss
+2 1 1 hit
+2 2 1 hit
+2 2 1 miss repeat
+1 10 3 hit
+1 10 4 sunk -- 4-cell vertical ship at 10 1
+1 10 5 miss
+2 5 5 repeat
+Player 1 won the game!
+";
+
+    #[test]
+    fn test_GameLog_parse() {
+        let game_log = GameLog::parse(LOG, &GameRules::default()).unwrap();
+        assert!(game_log.boards.is_some());
+        assert_eq!(game_log.shots.len(), 10);
+        assert_eq!(game_log.result, Some(GameResult::Player1Win));
+        assert!(game_log.notes.is_empty());
+
+        let shot = &game_log.shots[5];
+        assert_eq!((shot.player_number, shot.is_repeated), (2, true));
+        assert_eq!(shot.to_string(), "Player 2 shoots 2 1: miss (repeated)");
+        let shot = &game_log.shots[9];
+        assert_eq!(
+            shot.outcome.as_ref().err(),
+            Some(&RuleViolation::RepeatedShot)
+        );
+        assert_eq!(
+            game_log.shots[7].outcome.as_ref().unwrap().result,
+            GameBoardShotResult::Sunk
+        );
+        assert_eq!(
+            game_log.shots[7].sunk_ship_name.as_ref().unwrap(),
+            "4-cell vertical ship at 10 1"
+        );
+
+        // Logs written before the sunk ships were named
+        let old_log = LOG.replace(" -- 4-cell vertical ship at 10 1", "");
+        let game_log = GameLog::parse(&old_log, &GameRules::default()).unwrap();
+        assert_eq!(game_log.shots[7].to_string(), "Player 1 shoots 10 4: sunk");
+        assert!(game_log.shots[7].sunk_ship_name.is_none());
+
+        let game_log = GameLog::parse("Player 2 won the game!\n", &GameRules::default()).unwrap();
+        assert!(game_log.boards.is_none());
+        assert_eq!(game_log.result, Some(GameResult::Player2Win));
+
+        assert!(GameLog::parse(&LOG[11..], &GameRules::default()).is_err());
+        assert!(GameLog::parse(&LOG.replace("###_###", "####_##"), &GameRules::default()).is_err());
+    }
+
+    #[test]
+    fn test_log_shot() {
+        let rules = GameRules::default();
+        let game_log = GameLog::parse(LOG, &rules).unwrap();
+        let mut game = Game::new(&rules, game_log.boards.clone().unwrap(), Random::new(0));
+        let mut log = String::new();
+        for shot in &game_log.shots[..9] {
+            let logged_shot = log_shot(&mut log, &mut game, shot.position);
+            assert_eq!(logged_shot.to_string(), shot.to_string());
+        }
+        assert!(LOG.contains(&log));
+
+        let rules = GameRules {
+            repeat_shots: "forfeit".parse().unwrap(),
+            ..GameRules::default()
+        };
+        let mut game = Game::new(&rules, game_log.boards.unwrap(), Random::new(0));
+        let mut log = String::new();
+        let position = "1 1".parse().unwrap();
+        for _ in 0..3 {
+            log_shot(&mut log, &mut game, position);
+        }
+        assert_eq!(log, "1 1 1 miss\n2 1 1 hit\n2 1 1 repeat\n");
+    }
+
+    #[test]
+    fn test_GameLog_replay() {
+        let game_log = GameLog::parse(LOG, &GameRules::default()).unwrap();
+        let states = game_log.replay();
+        assert_eq!(states.len(), 11);
+        let [board1, board2] = &states[10];
+        assert_eq!((board1.hits_left(), board2.hits_left()), (18, 16));
+        assert_eq!(board2.ships_left(), 9);
+        assert!(board2.get("9 2".parse().unwrap()).is_shot());
+        assert!(!states[2][1].get("9 2".parse().unwrap()).is_shot());
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt::Write as _;
+
+use crate::position::Position;
+use crate::rules::GameRules;
+use crate::snapshot::{self, Canvas, Color, Glyph, Svg, BOARD_IMAGE_SIZE, CELL_SIZE};
+use crate::stats;
+use crate::GAME_BOARD_SIZE;
+
+/// Characters of the ASCII heatmap from the rarest to the most frequent cells.
+const SHADES: &[u8] = b" .:-=+*#%@";
+/// Height of the title above the board in SVG.
+const TITLE_HEIGHT: i32 = 32;
+
+/// Build heatmaps of where the bots place their ships and where they shoot first over a directory
+/// of game logs
+///
+/// The bots are identified by the names of the log files, as for the `stats` command.
+#[derive(Debug, structopt::StructOpt)]
+pub struct HeatmapArgs {
+    #[structopt(parse(from_os_str))]
+    dir: std::path::PathBuf,
+    /// Only build the heatmaps of this bot
+    #[structopt(long = "bot")]
+    bot: Option<String>,
+    /// How many first shots of every game are counted
+    #[structopt(long = "first", default_value = "10")]
+    first: usize,
+    /// Also save the heatmaps as `<bot>.placements.svg` and `<bot>.shots.svg` into this directory
+    #[structopt(long = "svg-dir", parse(from_os_str))]
+    svg_dir: Option<std::path::PathBuf>,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub fn heatmap(args: HeatmapArgs) -> Result<(), String> {
+    let logs = stats::read_logs(&args.dir, &args.rules)?;
+    // The ship placements and the first shots of every bot in the order they were met
+    let mut bots: Vec<(String, Heatmap, Heatmap)> = Vec::new();
+    for (names, game_log) in &logs {
+        let boards = match game_log.boards {
+            Some(ref boards) => boards,
+            None => continue,
+        };
+        for (seat, name) in names.iter().enumerate() {
+            if args.bot.iter().any(|bot| bot != name) {
+                continue;
+            }
+            let index = match bots.iter().position(|(bot, _, _)| bot == name) {
+                Some(index) => index,
+                None => {
+                    bots.push((name.clone(), Heatmap::default(), Heatmap::default()));
+                    bots.len() - 1
+                }
+            };
+            let (_, placements, shots) = &mut bots[index];
+            placements.add_sample(
+                Position::top_left()
+                    .iter()
+                    .filter(|&position| boards[seat].get(position).is_ship()),
+            );
+            shots.add_sample(
+                game_log
+                    .shots
+                    .iter()
+                    .filter(|shot| usize::from(shot.player_number) == seat + 1)
+                    .take(args.first)
+                    .map(|shot| shot.position),
+            );
+        }
+    }
+    if bots.is_empty() {
+        return Err("no boards of the bots are found in the logs".to_owned());
+    }
+
+    for (name, placements, shots) in &bots {
+        let placements_title = format!("{}: ships in {} boards", name, placements.samples());
+        let shots_title = format!(
+            "{}: first {} shots in {} games",
+            name,
+            args.first,
+            shots.samples()
+        );
+        println!("{}\n{}", placements_title, placements.to_ascii());
+        println!("{}\n{}", shots_title, shots.to_ascii());
+        if let Some(ref svg_dir) = args.svg_dir {
+            for (kind, heatmap, title) in &[
+                ("placements", placements, &placements_title),
+                ("shots", shots, &shots_title),
+            ] {
+                let path = svg_dir.join(format!("{}.{}.svg", name, kind));
+                std::fs::write(&path, heatmap.to_svg(title))
+                    .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How many times every cell was marked (a ship placed or a shot fired) in a number of samples
+/// (boards or games).
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    /// Indexed by `Position::yx`.
+    counts: Vec<u32>,
+    samples: u32,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; usize::from(GAME_BOARD_SIZE) * usize::from(GAME_BOARD_SIZE)],
+            samples: 0,
+        }
+    }
+}
+
+impl Heatmap {
+    /// Adds a sample with the marked cells.
+    pub fn add_sample<I: IntoIterator<Item = Position>>(&mut self, positions: I) {
+        self.samples += 1;
+        for position in positions {
+            self.counts[usize::from(position.yx())] += 1;
+        }
+    }
+
+    pub fn get(&self, position: Position) -> u32 {
+        self.counts[usize::from(position.yx())]
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// The count of the cell relative to the most marked cell, from 0 to 1.
+    pub fn intensity(&self, position: Position) -> f32 {
+        let max = self.counts.iter().cloned().max().unwrap_or(0);
+        if max == 0 {
+            0.0
+        } else {
+            self.get(position) as f32 / max as f32
+        }
+    }
+
+    /// The board with every cell shaded by `SHADES` (a space is a cell never marked) and the
+    /// coordinates around, without trailing spaces.
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = "  ".to_owned();
+        for x in 1..=GAME_BOARD_SIZE {
+            let _ = write!(ascii, "{:>3}", x);
+        }
+        ascii.push('\n');
+        for position in Position::top_left().iter() {
+            if position.x() == 0 {
+                let _ = write!(ascii, "{:>2}", position.y() + 1);
+            }
+            let shade = (self.intensity(position) * (SHADES.len() - 1) as f32).ceil() as usize;
+            ascii.push_str("  ");
+            ascii.push(char::from(SHADES[shade]));
+            if position.x() == GAME_BOARD_SIZE - 1 {
+                ascii.truncate(ascii.trim_end_matches(' ').len());
+                ascii.push('\n');
+            }
+        }
+        ascii
+    }
+
+    /// The board with every cell shaded in proportion to its count and labelled with the
+    /// percentage of the samples it was marked in.
+    pub fn to_svg(&self, title: &str) -> String {
+        let mut svg = Svg::new(BOARD_IMAGE_SIZE, TITLE_HEIGHT + BOARD_IMAGE_SIZE);
+        svg.rect(
+            0,
+            0,
+            BOARD_IMAGE_SIZE,
+            TITLE_HEIGHT + BOARD_IMAGE_SIZE,
+            Color::Background,
+        );
+        svg.text(
+            (BOARD_IMAGE_SIZE / 2, TITLE_HEIGHT / 2),
+            title,
+            13,
+            Color::Label,
+        );
+        let cells = usize::from(GAME_BOARD_SIZE) * usize::from(GAME_BOARD_SIZE);
+        snapshot::draw_board(&mut svg, (0, TITLE_HEIGHT), &vec![Glyph::Water; cells]);
+        for position in Position::top_left().iter() {
+            let count = self.get(position);
+            if count == 0 {
+                continue;
+            }
+            let x = CELL_SIZE * (i32::from(position.x()) + 1);
+            let y = TITLE_HEIGHT + CELL_SIZE * (i32::from(position.y()) + 1);
+            svg.translucent_rect(
+                x + 1,
+                y + 1,
+                CELL_SIZE - 1,
+                CELL_SIZE - 1,
+                Color::Hit,
+                self.intensity(position),
+            );
+            let percent = count * 100 / std::cmp::max(self.samples, 1);
+            svg.text(
+                (x + CELL_SIZE / 2, y + CELL_SIZE / 2),
+                &percent.to_string(),
+                9,
+                Color::Label,
+            );
+        }
+        svg.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_Heatmap() {
+        let position = |s: &str| s.parse::<Position>().unwrap();
+        let mut heatmap = Heatmap::default();
+        heatmap.add_sample(vec![position("1 1"), position("2 1")]);
+        heatmap.add_sample(vec![position("1 1"), position("10 10")]);
+        heatmap.add_sample(Vec::new());
+        assert_eq!(heatmap.samples(), 3);
+        assert_eq!(
+            (heatmap.get(position("1 1")), heatmap.get(position("5 5"))),
+            (2, 0)
+        );
+        assert_eq!(heatmap.intensity(position("2 1")), 0.5);
+
+        let ascii = heatmap.to_ascii();
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "    1  2  3  4  5  6  7  8  9 10");
+        assert_eq!(lines[1], " 1  @  +");
+        assert_eq!(lines[10], "10                             +");
+
+        let svg = heatmap.to_svg("bot: <ships>");
+        assert!(svg.contains("bot: &lt;ships&gt;"));
+        assert_eq!(svg.matches("fill-opacity").count(), 3);
+        assert!(svg.contains("fill-opacity=\"1.000\""));
+        assert!(svg.contains(">66</text>"));
+    }
+}
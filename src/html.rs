@@ -0,0 +1,304 @@
+use std::fmt::Write as _;
+
+use crate::board::GameBoard;
+use crate::game_log::GameLog;
+use crate::position::Position;
+use crate::rules::GameRules;
+use crate::GAME_BOARD_SIZE;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+.boards { display: flex; flex-wrap: wrap; gap: 3em; }
+table { border-collapse: collapse; }
+th { width: 1.8em; height: 1.8em; color: #888; font-weight: normal; }
+td { width: 1.8em; height: 1.8em; border: 1px solid #9ab; text-align: center; font-family: monospace; }
+td.empty { background: #eef6ff; }
+td.ship { background: #6b7b8c; color: #fff; }
+td.miss { background: #dde; color: #557; }
+td.hit { background: #e55; color: #fff; font-weight: bold; }
+td.mine { background: #fc6; }
+td.mine-hit { background: #c60; color: #fff; font-weight: bold; }
+td.last { outline: 3px solid #fa0; outline-offset: -3px; }
+.controls { margin: 1.5em 0; }
+.controls button { min-width: 3em; }
+.controls input { width: 20em; vertical-align: middle; }
+#shots { max-height: 20em; overflow-y: auto; font-family: monospace; }
+#shots li { cursor: pointer; }
+#shots li.current { background: #fc6; }
+#shots li.future { color: #aaa; }
+.verdict { font-size: 1.5em; font-weight: bold; }
+";
+
+const SCRIPT: &str = "
+(function () {
+  var boards = [document.getElementById('board1'), document.getElementById('board2')];
+  var shots = document.querySelectorAll('#shots li');
+  var slider = document.getElementById('step');
+  var counter = document.getElementById('counter');
+  var playButton = document.getElementById('play');
+  var classes = { '_': 'empty', 'O': 'miss', '$': 'hit', '*': 'mine', 'X': 'mine-hit' };
+  var step = STEPS.length - 1;
+  var timer = null;
+
+  function show(newStep) {
+    step = Math.max(0, Math.min(STEPS.length - 1, newStep));
+    for (var board = 0; board < 2; board++) {
+      var cells = boards[board].querySelectorAll('td');
+      for (var i = 0; i < cells.length; i++) {
+        var cell = STEPS[step][board].charAt(i);
+        cells[i].className = classes[cell] || 'ship';
+        cells[i].textContent = cell === '_' ? '' : cell;
+      }
+    }
+    if (step > 0) {
+      var shot = SHOTS[step - 1];
+      boards[2 - shot[0]].querySelectorAll('td')[shot[1]].className += ' last';
+    }
+    for (var i = 0; i < shots.length; i++) {
+      shots[i].className = i === step - 1 ? 'current' : (i < step ? '' : 'future');
+    }
+    slider.value = step;
+    counter.textContent = step + ' / ' + (STEPS.length - 1);
+  }
+
+  function stop() {
+    clearInterval(timer);
+    timer = null;
+    playButton.textContent = 'Play';
+  }
+
+  function play() {
+    if (timer !== null) {
+      stop();
+      return;
+    }
+    if (step === STEPS.length - 1) {
+      show(0);
+    }
+    playButton.textContent = 'Pause';
+    timer = setInterval(function () {
+      show(step + 1);
+      if (step === STEPS.length - 1) {
+        stop();
+      }
+    }, 400);
+  }
+
+  document.getElementById('first').onclick = function () { stop(); show(0); };
+  document.getElementById('prev').onclick = function () { stop(); show(step - 1); };
+  document.getElementById('next').onclick = function () { stop(); show(step + 1); };
+  document.getElementById('last').onclick = function () { stop(); show(STEPS.length - 1); };
+  playButton.onclick = play;
+  slider.oninput = function () { stop(); show(parseInt(slider.value, 10)); };
+  for (var i = 0; i < shots.length; i++) {
+    shots[i].onclick = (function (shotStep) {
+      return function () { stop(); show(shotStep); };
+    })(i + 1);
+  }
+  document.onkeydown = function (event) {
+    if (event.key === 'ArrowLeft') { stop(); show(step - 1); }
+    if (event.key === 'ArrowRight') { stop(); show(step + 1); }
+  };
+  show(step);
+})();
+";
+
+/// Render a finished game from its log (the output of the judge) into a self-contained HTML
+/// report with a timeline of the shots
+#[derive(Debug, structopt::StructOpt)]
+pub struct RenderHtmlArgs {
+    #[structopt(parse(from_os_str))]
+    log: std::path::PathBuf,
+    /// Where to write the report (the log path with the `.html` extension by default)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<std::path::PathBuf>,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub fn render_html(args: RenderHtmlArgs) -> Result<(), String> {
+    let log = std::fs::read_to_string(&args.log)
+        .map_err(|err| format!("failed to read {}: {}", args.log.display(), err))?;
+    let game_log = GameLog::parse(&log, &args.rules)
+        .map_err(|err| format!("failed to parse {}: {}", args.log.display(), err))?;
+    let title = match args.log.file_name() {
+        Some(file_name) => format!("Game report: {}", file_name.to_string_lossy()),
+        None => "Game report".to_owned(),
+    };
+    let output = match args.output {
+        Some(output) => output,
+        None => args.log.with_extension("html"),
+    };
+    std::fs::write(&output, report(&game_log, &title))
+        .map_err(|err| format!("failed to write {}: {}", output.display(), err))?;
+    println!("The report is saved to {}", output.display());
+    Ok(())
+}
+
+/// Builds the HTML report. It shows the final state of the boards, which the embedded script
+/// can step back and forth through the shots.
+pub fn report(game_log: &GameLog, title: &str) -> String {
+    let states = game_log.replay();
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(title));
+    let _ = writeln!(html, "<style>{}</style>", STYLE);
+    html.push_str("</head>\n<body>\n");
+    let _ = writeln!(html, "<h1>{}</h1>", escape(title));
+
+    if let Some(final_boards) = states.last() {
+        let last_shot = game_log.shots.last();
+        html.push_str("<div class=\"boards\">\n");
+        for (index, board) in final_boards.iter().enumerate() {
+            let player_number = index as u8 + 1;
+            let last_shot_position = last_shot
+                .filter(|shot| shot.player_number != player_number)
+                .map(|shot| shot.position.yx());
+            let _ = writeln!(
+                html,
+                "<div><h2>Player {}</h2>\n{}</div>",
+                player_number,
+                board_table(
+                    &format!("board{}", player_number),
+                    board,
+                    last_shot_position
+                )
+            );
+        }
+        html.push_str("</div>\n");
+
+        html.push_str(
+            "<div class=\"controls\">\
+             <button id=\"first\">|&lt;</button> \
+             <button id=\"prev\">&lt;</button> \
+             <button id=\"play\">Play</button> \
+             <button id=\"next\">&gt;</button> \
+             <button id=\"last\">&gt;|</button> ",
+        );
+        let _ = writeln!(
+            html,
+            "<input type=\"range\" id=\"step\" min=\"0\" max=\"{0}\" value=\"{0}\"> \
+             <span id=\"counter\">{0} / {0}</span></div>",
+            game_log.shots.len()
+        );
+
+        html.push_str("<ol id=\"shots\">\n");
+        for shot in &game_log.shots {
+            let _ = writeln!(html, "<li>{}</li>", escape(&shot.to_string()));
+        }
+        html.push_str("</ol>\n");
+    }
+
+    for note in &game_log.notes {
+        let _ = writeln!(html, "<p>{}</p>", escape(note));
+    }
+    let verdict = match game_log.result {
+        Some(game_result) => game_result.as_str(),
+        None => "The game is not finished",
+    };
+    let _ = writeln!(html, "<p class=\"verdict\">{}</p>", verdict);
+
+    if !states.is_empty() {
+        html.push_str("<script>\nvar STEPS = [\n");
+        for boards in &states {
+            let _ = writeln!(
+                html,
+                "[\"{}\", \"{}\"],",
+                board_cells(&boards[0]),
+                board_cells(&boards[1])
+            );
+        }
+        html.push_str("];\nvar SHOTS = [");
+        for shot in &game_log.shots {
+            let _ = write!(html, "[{}, {}],", shot.player_number, shot.position.yx());
+        }
+        html.push_str("];\n");
+        html.push_str(SCRIPT);
+        html.push_str("</script>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// The cells of the board row by row as they are printed in the game log.
+fn board_cells(board: &GameBoard) -> String {
+    board.to_string().replace('\n', "")
+}
+
+fn board_table(id: &str, board: &GameBoard, last_shot: Option<u8>) -> String {
+    let mut table = format!("<table id=\"{}\">\n<tr><th></th>", id);
+    for x in 1..=GAME_BOARD_SIZE {
+        let _ = write!(table, "<th>{}</th>", x);
+    }
+    table.push_str("</tr>\n");
+    for (position, cell) in Position::top_left().iter().zip(board_cells(board).chars()) {
+        if position.x() == 0 {
+            let _ = write!(table, "<tr><th>{}</th>", position.y() + 1);
+        }
+        let class = match cell {
+            '_' => "empty",
+            'O' => "miss",
+            '$' => "hit",
+            '*' => "mine",
+            'X' => "mine-hit",
+            _ => "ship",
+        };
+        let marker = if last_shot == Some(position.yx()) {
+            " last"
+        } else {
+            ""
+        };
+        let glyph = if cell == '_' { ' ' } else { cell };
+        let _ = write!(table, "<td class=\"{}{}\">{}</td>", class, marker, glyph);
+        if position.x() == GAME_BOARD_SIZE - 1 {
+            table.push_str("</tr>\n");
+        }
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_report() {
+        let log = fixtures::log_boards()
+            + "1 1 1 hit\n1 2 1 miss\n2 1 1 hit\n2 2 1 hit\n2 5 1 miss\n\
+                   Shots limit of 3 per player is reached\n\
+                   Draw!\n";
+        let game_log = GameLog::parse(&log, &GameRules::default()).unwrap();
+        let html = report(&game_log, "<game>");
+        assert!(html.contains("<title>&lt;game&gt;</title>"));
+        assert!(html.contains("<td class=\"miss last\">O</td>"));
+        assert!(html.contains("<li>Player 2 shoots 5 1: miss</li>"));
+        assert!(html.contains("<p>Shots limit of 3 per player is reached</p>"));
+        assert!(html.contains("<p class=\"verdict\">Draw!</p>"));
+        assert!(html.contains("[\"$$##______"));
+        assert_eq!(html.matches("\"],\n").count(), 6);
+        assert!(!html.contains("http"));
+
+        let game_log = GameLog::parse("Player 1 won the game!", &GameRules::default()).unwrap();
+        let html = report(&game_log, "game");
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("Player 1 won the game!"));
+    }
+}
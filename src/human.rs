@@ -0,0 +1,537 @@
+use std::io::Write as _;
+
+use crate::board::{GameBoard, ShotReport};
+use crate::fleet::ShipShape;
+use crate::game::{Game, Turn};
+use crate::knowledge::KnowledgeBoard;
+use crate::placement::Placement;
+use crate::player::{OutputLimits, Player, StderrCapture};
+use crate::position::Position;
+use crate::random::Random;
+use crate::rules::GameRules;
+use crate::terminal::{Key, Keys, RawMode};
+use crate::{GameResult, GAME_BOARD_SIZE};
+
+const PLACEMENT_HELP: &str = "arrows or w/a/s/d - move, r - rotate, space - place a ship or a \
+                              mine, u - undo, x - place the rest randomly, Enter - start, q - quit";
+const BATTLE_HELP: &str = "arrows or w/a/s/d - move, space or Enter - fire at the cursor \
+                           (aim or cancel aiming in a volley), q - quit";
+
+/// Play against a bot in the terminal: place your ships and then shoot at the bot's board
+#[derive(Debug, structopt::StructOpt)]
+pub struct PlayHumanArgs {
+    #[structopt(parse(from_os_str))]
+    bot: std::path::PathBuf,
+    /// Save stderr of the bot into this file, so it does not mess up the screen
+    #[structopt(long = "bot-stderr", default_value = "bot.stderr", parse(from_os_str))]
+    bot_stderr: std::path::PathBuf,
+    /// Maximum number of bytes of stderr of the bot to save
+    #[structopt(long = "stderr-limit", default_value = "65536")]
+    stderr_limit: usize,
+    #[structopt(flatten)]
+    output_limits: OutputLimits,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+/// Lets the human (player 1) place their ships and play against the bot (player 2).
+pub async fn play_human(args: PlayHumanArgs) -> Result<GameResult, String> {
+    args.rules.check()?;
+    let _raw_mode = RawMode::enable();
+    let mut keys = Keys::spawn();
+    let human_board = match place_ships(&args.rules, &mut keys).await {
+        Some(human_board) => human_board,
+        None => return Ok(GameResult::Player2Win),
+    };
+
+    let stderr_capture = StderrCapture {
+        path: args.bot_stderr.clone(),
+        limit: args.stderr_limit,
+    };
+    let (mut bot, bot_board) = match Player::init(
+        &args.bot,
+        Some(stderr_capture),
+        args.output_limits,
+        &args.rules,
+    )
+    .await
+    {
+        Ok(bot) => bot,
+        Err(_) => {
+            println!("The bot failed to place its ships");
+            return Ok(GameResult::Player1Win);
+        }
+    };
+
+    let mut game = Game::new(&args.rules, [human_board, bot_board], Random::from_time());
+    let mut battle = Battle {
+        cursor: Position::top_left(),
+        knowledge: KnowledgeBoard::default(),
+        messages: Vec::new(),
+        keys,
+    };
+    battle.play(&mut game, &mut bot).await;
+    battle.render_result(game.board(0), game.board(1));
+    Ok(game.result().expect("the battle is over"))
+}
+
+/// Ships the human has placed so far and the ship that is being placed at the cursor.
+struct PlacementScreen<'a> {
+    placement: Placement<'a>,
+    cursor: Position,
+    /// Index of the rotation/reflection of the ship being placed.
+    variant_index: usize,
+}
+
+impl<'a> PlacementScreen<'a> {
+    fn new(rules: &'a GameRules) -> Self {
+        Self {
+            placement: Placement::new(rules),
+            cursor: Position::top_left(),
+            variant_index: 0,
+        }
+    }
+
+    fn current_shape(&self) -> Option<ShipShape> {
+        let variants = self.placement.next_ship()?.variants();
+        Some(variants[self.variant_index % variants.len()].clone())
+    }
+
+    /// Returns the cells of the ship being placed at the cursor, or why it cannot be placed there.
+    fn ship_at_cursor(&self) -> Result<(Vec<Position>, ShipShape), &'static str> {
+        let shape = self.current_shape().ok_or("all the ships are placed")?;
+        let cells = shape
+            .place_at(self.cursor)
+            .ok_or("the ship does not fit the board")?;
+        if self.placement.accepts(&cells, &shape) {
+            Ok((cells, shape))
+        } else {
+            Err("the ship cannot be placed here")
+        }
+    }
+
+    /// Returns why the mine cannot be placed at the cursor, if it cannot.
+    fn mine_at_cursor(&self) -> Result<(), &'static str> {
+        if self.placement.pending_mines() == 0 {
+            Err("all the mines are placed")
+        } else if !self.placement.is_empty(self.cursor) {
+            Err("the cell is not empty")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Places the ship being placed at the cursor or, once all the ships are placed, a mine.
+    fn place(&mut self) -> Result<(), &'static str> {
+        if self.placement.next_ship().is_none() {
+            self.mine_at_cursor()?;
+            self.placement.try_place_mine(self.cursor);
+            return Ok(());
+        }
+        let (cells, shape) = self.ship_at_cursor()?;
+        self.placement.try_place(cells, shape);
+        self.variant_index = 0;
+        Ok(())
+    }
+
+    fn undo(&mut self) {
+        self.placement.undo();
+        self.variant_index = 0;
+    }
+
+    /// Renders the placed ships with `#`, the placed mines with `*` and the ship or the mine being
+    /// placed at the cursor with `+`, or with `!` if it cannot be placed there.
+    fn cells(&self) -> Vec<char> {
+        let mut cells = vec!['_'; usize::from(GAME_BOARD_SIZE * GAME_BOARD_SIZE)];
+        for position in self.placement.placed_ships().iter().flatten() {
+            cells[usize::from(position.yx())] = '#';
+        }
+        for position in self.placement.mines() {
+            cells[usize::from(position.yx())] = '*';
+        }
+        if self.placement.next_ship().is_none() {
+            if self.placement.pending_mines() > 0 {
+                let mark = if self.mine_at_cursor().is_ok() {
+                    '+'
+                } else {
+                    '!'
+                };
+                cells[usize::from(self.cursor.yx())] = mark;
+            }
+            return cells;
+        }
+        let (ship, mark) = match self.ship_at_cursor() {
+            Ok((ship, _)) => (ship, '+'),
+            Err(_) => (
+                self.current_shape()
+                    .and_then(|shape| shape.place_at(self.cursor))
+                    .unwrap_or_else(|| vec![self.cursor]),
+                '!',
+            ),
+        };
+        for position in ship {
+            cells[usize::from(position.yx())] = mark;
+        }
+        cells
+    }
+}
+
+async fn place_ships(rules: &GameRules, keys: &mut Keys) -> Option<GameBoard> {
+    let mut screen = PlacementScreen::new(rules);
+    let mut random = Random::from_time();
+    let mut message = String::new();
+    loop {
+        let board = screen.placement.board();
+        let status = if board.is_some() {
+            "All the ships are placed, press Enter to start the game".to_owned()
+        } else if screen.placement.next_ship().is_none() {
+            match screen.mine_at_cursor() {
+                Ok(()) => format!("Place a mine ({} left)", screen.placement.pending_mines()),
+                Err(err) => format!("Cannot place the mine: {}", err),
+            }
+        } else {
+            match screen.ship_at_cursor() {
+                Ok((ship, _)) => format!("Place a ship of {} cells", ship.len()),
+                Err(err) => format!("Cannot place the ship: {}", err),
+            }
+        };
+        render(
+            &[("Your board", screen.cells(), Some(screen.cursor))],
+            &[PLACEMENT_HELP.to_owned(), status, message.clone()],
+        );
+        message.clear();
+        let key = keys.next().await?;
+        if let Some(cursor) = move_cursor(screen.cursor, key) {
+            screen.cursor = cursor;
+            continue;
+        }
+        match key {
+            Key::Enter => match board {
+                Some(board) => return Some(board),
+                None => message = "Place all the ships first".to_owned(),
+            },
+            Key::Char('r') => screen.variant_index += 1,
+            Key::Char(' ') | Key::Char('p') => {
+                if let Err(err) = screen.place() {
+                    message = format!("Cannot place it: {}", err);
+                }
+            }
+            Key::Char('u') => screen.undo(),
+            Key::Char('x') => {
+                if !screen.placement.auto_place(&mut random) {
+                    message = "Failed to place the rest of the ships and mines".to_owned();
+                }
+            }
+            Key::Char('q') | Key::Interrupt => return None,
+            _ => message = "Unknown key".to_owned(),
+        }
+    }
+}
+
+struct Battle {
+    cursor: Position,
+    /// What the human knows about the bot's board from the reports of their shots.
+    knowledge: KnowledgeBoard,
+    messages: Vec<String>,
+    keys: Keys,
+}
+
+impl Battle {
+    /// Plays the game of the human (player 1) against the bot (player 2) to the end.
+    async fn play(&mut self, game: &mut Game<'_, GameBoard>, bot: &mut Player) {
+        while let Some(turn) = game.turn() {
+            let player = turn.player();
+            let shot_positions = if player == 0 {
+                let volley_size = match turn {
+                    Turn::Shot { .. } => 1,
+                    Turn::Volley { size, .. } => size,
+                };
+                match self
+                    .next_volley(volley_size, game.board(0), game.board(1))
+                    .await
+                {
+                    Some(shot_positions) => shot_positions,
+                    None => return game.forfeit(),
+                }
+            } else {
+                let shot_positions = match turn {
+                    Turn::Shot { .. } => bot
+                        .next_shot_position()
+                        .await
+                        .map(|shot_position| vec![shot_position]),
+                    Turn::Volley { size, .. } => bot.next_volley(size).await,
+                };
+                match shot_positions {
+                    Some(shot_positions) => shot_positions,
+                    None => {
+                        self.messages
+                            .push("The bot failed to make a shot, you won!".to_owned());
+                        return game.forfeit();
+                    }
+                }
+            };
+            let mut shot_reports = Vec::with_capacity(shot_positions.len());
+            for shot_position in shot_positions {
+                match self.shoot(game, player, shot_position) {
+                    Some(shot_report) => shot_reports.push(shot_report),
+                    None => return,
+                }
+            }
+            if player == 0 {
+                for shot_report in &shot_reports {
+                    if let Some(revealed_cell) = shot_report.revealed_cell {
+                        bot.send_revealed_cell(revealed_cell).await;
+                    }
+                }
+            } else {
+                match turn {
+                    Turn::Shot { .. } => bot.reply_shot_result(&shot_reports[0]).await,
+                    Turn::Volley { .. } => {
+                        bot.reply_volley_results(&shot_reports, game.rules()).await
+                    }
+                }
+            }
+        }
+        if game.is_adjudicated() {
+            self.messages.push(format!(
+                "Shots limit of {} is reached (you have {} hits left, the bot has {} hits left)",
+                game.rules().max_shots,
+                game.hits_left(0),
+                game.hits_left(1)
+            ));
+        }
+        match game.winner() {
+            Some(0) => self.messages.push("You won!".to_owned()),
+            Some(_) => self.messages.push("The bot won!".to_owned()),
+            None => (),
+        }
+    }
+
+    /// Makes the shot of the player and tells the human about it. Returns `None` if the shot
+    /// violates the rules, which ends the game.
+    fn shoot(
+        &mut self,
+        game: &mut Game<'_, GameBoard>,
+        player: usize,
+        shot_position: Position,
+    ) -> Option<ShotReport> {
+        let shooter = if player == 0 { "You" } else { "The bot" };
+        let shot_report = match game.shoot(shot_position) {
+            Ok(shot_report) => shot_report,
+            Err(violation) => {
+                let winner = if player == 0 { "the bot" } else { "you" };
+                self.messages.push(format!(
+                    "{} violated the rules ({}), {} won!",
+                    shooter,
+                    violation.as_str(),
+                    winner
+                ));
+                return None;
+            }
+        };
+        let shooter = if player == 0 {
+            "You shoot"
+        } else {
+            "The bot shoots"
+        };
+        self.messages
+            .push(format!("{} {}: {}", shooter, shot_position, shot_report));
+        if player == 0 {
+            self.knowledge.update(shot_position, &shot_report);
+        } else if let Some(revealed_cell) = shot_report.revealed_cell {
+            self.knowledge.reveal(revealed_cell);
+        }
+        Some(shot_report)
+    }
+
+    /// Lets the human move the cursor over the bot's board and aim at the cells that have not been
+    /// shot yet until the volley is complete; aiming at an aimed cell again cancels that. Returns
+    /// `None` if the human quits the game.
+    async fn next_volley(
+        &mut self,
+        volley_size: u8,
+        human_board: &GameBoard,
+        bot_board: &GameBoard,
+    ) -> Option<Vec<Position>> {
+        let mut targets = Vec::with_capacity(usize::from(volley_size));
+        loop {
+            let status = if volley_size > 1 {
+                format!(
+                    "Aim {} more shots of the volley",
+                    usize::from(volley_size) - targets.len()
+                )
+            } else {
+                String::new()
+            };
+            self.render(human_board, &targets, &status);
+            let key = self.keys.next().await?;
+            if let Some(cursor) = move_cursor(self.cursor, key) {
+                self.cursor = cursor;
+                continue;
+            }
+            match key {
+                Key::Enter | Key::Char(' ') | Key::Char('f') => {
+                    if bot_board.get(self.cursor).is_shot() {
+                        self.messages
+                            .push(format!("You have already shot at {}", self.cursor));
+                    } else if let Some(index) = targets.iter().position(|&t| t == self.cursor) {
+                        targets.remove(index);
+                    } else {
+                        targets.push(self.cursor);
+                        if targets.len() == usize::from(volley_size) {
+                            return Some(targets);
+                        }
+                    }
+                }
+                Key::Char('q') | Key::Interrupt => return None,
+                _ => self.messages.push("Unknown key".to_owned()),
+            }
+        }
+    }
+
+    /// Renders both boards while the human aims: the bot's board only shows what the shot reports
+    /// told so far and the cells aimed at with `+`.
+    fn render(&self, human_board: &GameBoard, targets: &[Position], status: &str) {
+        let bot_cells = Position::top_left()
+            .iter()
+            .map(|position| {
+                if targets.contains(&position) {
+                    '+'
+                } else {
+                    char::from(self.knowledge.get(position))
+                }
+            })
+            .collect();
+        let mut messages = self.recent_messages();
+        messages.push(status.to_owned());
+        messages.push(BATTLE_HELP.to_owned());
+        render(
+            &[
+                ("Your board", board_cells(human_board), None),
+                ("The bot's board", bot_cells, Some(self.cursor)),
+            ],
+            &messages,
+        );
+    }
+
+    /// Renders both boards with all the ships once the game is over.
+    fn render_result(&self, human_board: &GameBoard, bot_board: &GameBoard) {
+        render(
+            &[
+                ("Your board", board_cells(human_board), None),
+                ("The bot's board", board_cells(bot_board), None),
+            ],
+            &self.recent_messages(),
+        );
+    }
+
+    fn recent_messages(&self) -> Vec<String> {
+        const MESSAGES_SHOWN: usize = 8;
+        let skipped_messages = self.messages.len().saturating_sub(MESSAGES_SHOWN);
+        self.messages[skipped_messages..].to_vec()
+    }
+}
+
+fn board_cells(board: &GameBoard) -> Vec<char> {
+    Position::top_left()
+        .iter()
+        .map(|position| char::from(board.get(position)))
+        .collect()
+}
+
+/// Moves the cursor if the key is an arrow or one of `w`, `a`, `s`, `d`. The cursor stays at the
+/// edge of the board.
+fn move_cursor(cursor: Position, key: Key) -> Option<Position> {
+    let moved_cursor = match key {
+        Key::Up | Key::Char('w') => cursor.get_above(),
+        Key::Left | Key::Char('a') => cursor.get_left(),
+        Key::Down | Key::Char('s') => cursor.get_below(),
+        Key::Right | Key::Char('d') => cursor.get_right(),
+        _ => return None,
+    };
+    Some(moved_cursor.unwrap_or(cursor))
+}
+
+/// Clears the terminal and draws the boards side by side, marking the cursor with brackets.
+fn render(boards: &[(&str, Vec<char>, Option<Position>)], messages: &[String]) {
+    const BOARD_WIDTH: usize = 3 + 3 * GAME_BOARD_SIZE as usize;
+    const BOARDS_SPACING: &str = "    ";
+
+    let mut screen = String::from("\x1b[2J\x1b[H");
+    let titles: Vec<String> = boards
+        .iter()
+        .map(|(title, _, _)| format!("{:width$}", title, width = BOARD_WIDTH))
+        .collect();
+    screen.push_str(titles.join(BOARDS_SPACING).trim_end());
+    screen.push('\n');
+    let header: String = (1..=GAME_BOARD_SIZE).map(|x| format!("{:^3}", x)).collect();
+    let headers = vec![format!("   {}", header); boards.len()];
+    screen.push_str(headers.join(BOARDS_SPACING).trim_end());
+    screen.push('\n');
+    for y in 0..GAME_BOARD_SIZE {
+        let rows: Vec<String> = boards
+            .iter()
+            .map(|(_, cells, cursor)| {
+                let mut row = format!("{:>2} ", y + 1);
+                for x in 0..GAME_BOARD_SIZE {
+                    let position = Position::from_xy(x, y).unwrap();
+                    let cell = cells[usize::from(position.yx())];
+                    if Some(position) == *cursor {
+                        row.push_str(&format!("[{}]", cell));
+                    } else {
+                        row.push_str(&format!(" {} ", cell));
+                    }
+                }
+                row
+            })
+            .collect();
+        screen.push_str(rows.join(BOARDS_SPACING).trim_end());
+        screen.push('\n');
+    }
+    screen.push('\n');
+    for message in messages {
+        screen.push_str(message);
+        screen.push('\n');
+    }
+    print!("{}", screen);
+    let _ = std::io::stdout().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_PlacementScreen_place() {
+        let rules = GameRules::default();
+        let mut screen = PlacementScreen::new(&rules);
+        assert_eq!(screen.current_shape().map(|shape| shape.size()), Some(4));
+        screen.cursor = "8 1".parse().unwrap();
+        assert_eq!(
+            screen.ship_at_cursor().unwrap_err(),
+            "the ship does not fit the board"
+        );
+        assert_eq!(screen.cells()[7..10].to_vec(), vec!['!', '_', '_']);
+        assert!(screen.place().is_err());
+        screen.variant_index += 1;
+        assert!(screen.ship_at_cursor().is_ok());
+        assert_eq!(screen.cells()[7..10].to_vec(), vec!['+', '_', '_']);
+        assert!(screen.place().is_ok());
+        assert_eq!(screen.current_shape().map(|shape| shape.size()), Some(3));
+
+        // Ships cannot touch each other
+        screen.cursor = "5 1".parse().unwrap();
+        assert_eq!(screen.cells()[4..8].to_vec(), vec!['!', '!', '!', '#']);
+        assert!(screen.place().is_err());
+        screen.cursor = "1 1".parse().unwrap();
+        assert!(screen.place().is_ok());
+        assert_eq!(screen.placement.placed_ships().len(), 2);
+
+        screen.variant_index += 1;
+        screen.undo();
+        assert_eq!(screen.variant_index, 0);
+        assert_eq!(screen.placement.placed_ships().len(), 1);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::board::{GameBoard, GameBoardShotResult, ShotReport};
+use crate::cells::{GameBoardCell, GameBoardCellState};
+use crate::position::Position;
+use crate::GAME_BOARD_SIZE;
+
+/// What the shooter knows about a cell of the opponent's board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KnowledgeCell {
+    Unknown,
+    Miss,
+    Hit,
+    Sunk,
+    Mine,
+    /// A ship cell revealed as a penalty for hitting a mine that has not been shot yet.
+    Revealed,
+}
+
+impl From<KnowledgeCell> for char {
+    fn from(cell: KnowledgeCell) -> Self {
+        match cell {
+            KnowledgeCell::Unknown => '.',
+            KnowledgeCell::Miss => 'O',
+            KnowledgeCell::Hit => '$',
+            KnowledgeCell::Sunk => '#',
+            KnowledgeCell::Mine => '*',
+            KnowledgeCell::Revealed => '@',
+        }
+    }
+}
+
+/// The opponent's board as the shooter sees it ("fog of war"): only the results of the shots
+/// are known, while the untouched ships stay hidden.
+#[derive(Clone, PartialEq, Eq)]
+pub struct KnowledgeBoard {
+    cells: Vec<KnowledgeCell>,
+}
+
+impl Default for KnowledgeBoard {
+    fn default() -> Self {
+        Self {
+            cells: vec![KnowledgeCell::Unknown; usize::from(GAME_BOARD_SIZE * GAME_BOARD_SIZE)],
+        }
+    }
+}
+
+impl KnowledgeBoard {
+    pub fn get(&self, position: Position) -> KnowledgeCell {
+        self.cells[usize::from(position.yx())]
+    }
+
+    fn set(&mut self, position: Position, cell: KnowledgeCell) {
+        self.cells[usize::from(position.yx())] = cell;
+    }
+
+    /// Marks the opponent's ship cell revealed to the shooter as a penalty for hitting a mine.
+    pub fn reveal(&mut self, position: Position) {
+        if self.get(position) == KnowledgeCell::Unknown {
+            self.set(position, KnowledgeCell::Revealed);
+        }
+    }
+
+    /// Updates the knowledge with the shot result as it is reported to the shooter.
+    pub fn update(&mut self, position: Position, shot_report: &ShotReport) {
+        match shot_report.result {
+            GameBoardShotResult::Miss => {
+                if self.get(position) == KnowledgeCell::Unknown {
+                    self.set(position, KnowledgeCell::Miss);
+                }
+            }
+            GameBoardShotResult::Hit => self.set(position, KnowledgeCell::Hit),
+            GameBoardShotResult::Mine => self.set(position, KnowledgeCell::Mine),
+            GameBoardShotResult::Sunk => {
+                if shot_report.sunk_ship.is_empty() {
+                    // Without the cells of the sunk ship reported, the ship is made of the hit
+                    // cells adjacent to the last shot.
+                    self.set(position, KnowledgeCell::Sunk);
+                    let mut ship = vec![position];
+                    while let Some(cell) = ship.pop() {
+                        for adjacent in [
+                            cell.get_left(),
+                            cell.get_right(),
+                            cell.get_above(),
+                            cell.get_below(),
+                        ]
+                        .iter()
+                        .flatten()
+                        {
+                            if self.get(*adjacent) == KnowledgeCell::Hit {
+                                self.set(*adjacent, KnowledgeCell::Sunk);
+                                ship.push(*adjacent);
+                            }
+                        }
+                    }
+                } else {
+                    for &cell in &shot_report.sunk_ship {
+                        self.set(cell, KnowledgeCell::Sunk);
+                    }
+                    for neighbour in shot_report
+                        .sunk_ship
+                        .iter()
+                        .flat_map(|cell| cell.neighbours())
+                    {
+                        if self.get(neighbour) == KnowledgeCell::Unknown {
+                            self.set(neighbour, KnowledgeCell::Miss);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Renders the board the same way players send their maps, with `.` for the unknown cells, `O`
+/// for misses, `$` for hits, `#` for sunk ships, `*` for mines and `@` for revealed ship cells.
+impl std::fmt::Display for KnowledgeBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(usize::from(GAME_BOARD_SIZE)) {
+            let line: String = row.iter().map(|&cell| char::from(cell)).collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shows the board the way the opponent sees it after all the shots made so far.
+impl From<&GameBoard> for KnowledgeBoard {
+    fn from(board: &GameBoard) -> Self {
+        let mut knowledge = Self::default();
+        for position in Position::top_left().iter() {
+            let cell = match board.get(position) {
+                GameBoardCell::Empty(GameBoardCellState::Shot) => KnowledgeCell::Miss,
+                GameBoardCell::Mine(GameBoardCellState::Shot) => KnowledgeCell::Mine,
+                GameBoardCell::Ship(GameBoardCellState::Shot) => match board.ship_at(position) {
+                    Some(ship) if ship.is_sunk() => KnowledgeCell::Sunk,
+                    _ => KnowledgeCell::Hit,
+                },
+                GameBoardCell::Empty(GameBoardCellState::NonShot)
+                | GameBoardCell::Ship(GameBoardCellState::NonShot)
+                | GameBoardCell::Mine(GameBoardCellState::NonShot) => KnowledgeCell::Unknown,
+            };
+            knowledge.set(position, cell);
+        }
+        knowledge
+    }
+}
+
+impl std::fmt::Debug for KnowledgeBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KnowledgeBoard:\n{}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures::board;
+
+    #[test]
+    fn test_KnowledgeBoard_from_board() {
+        let mut board = board();
+        assert_eq!(
+            KnowledgeBoard::from(&board).to_string(),
+            "..........\n".repeat(10)
+        );
+        for position in &["1 1", "2 1", "3 1", "4 1", "5 1", "1 3", "3 5"] {
+            board.shoot(position.parse().unwrap());
+        }
+        assert_eq!(
+            KnowledgeBoard::from(&board).to_string(),
+            "\
+             ####O.....\n\
+             ..........\n\
+             $.........\n\
+             ..........\n\
+             ..O.......\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             "
+        );
+    }
+
+    #[test]
+    fn test_KnowledgeBoard_update() {
+        for &reveal_sunk in &[false, true] {
+            let mut board = board();
+            let mut knowledge = KnowledgeBoard::default();
+            for position in &["1 1", "2 1", "4 1", "10 10", "3 1", "2 1", "1 7", "5 3"] {
+                let position: Position = position.parse().unwrap();
+                let mut shot_report = ShotReport::from(board.shoot(position));
+                if shot_report.result == GameBoardShotResult::Sunk && reveal_sunk {
+                    shot_report.sunk_ship = board.reveal_around_ship(position);
+                }
+                knowledge.update(position, &shot_report);
+            }
+            assert_eq!(knowledge, KnowledgeBoard::from(&board));
+        }
+    }
+
+    #[test]
+    fn test_KnowledgeBoard_reveal() {
+        let mut board = board();
+        let mut knowledge = KnowledgeBoard::default();
+        let position: Position = "1 1".parse().unwrap();
+        knowledge.update(position, &ShotReport::from(board.shoot(position)));
+        knowledge.reveal(position);
+        knowledge.reveal("2 1".parse().unwrap());
+        assert_eq!(&knowledge.to_string()[..11], "$@........\n");
+
+        let position: Position = "2 1".parse().unwrap();
+        knowledge.update(position, &ShotReport::from(board.shoot(position)));
+        assert_eq!(knowledge.get(position), KnowledgeCell::Hit);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::bitboard::{self, Mask};
+use crate::cells;
+use crate::fleet::ShipShape;
+use crate::position::Position;
+use crate::rules::{GameRules, PlacementRule};
+use crate::{InvalidInputError, GAME_BOARD_SIZE};
+
+/// The ships and the mines of a board the way the player placed them, validated by the rules.
+/// Both `GameBoard` and `BitBoard` are built from it, so they accept the same boards.
+pub struct Layout {
+    /// The cells of every ship ordered by their top-left cell, and the character the ship was
+    /// marked with (`#` or a letter).
+    pub ships: Vec<(char, Mask)>,
+    pub mines: Mask,
+}
+
+impl Layout {
+    /// Parses the board and validates it by the rules, see `GameBoard::from_lines_with_rules`.
+    pub fn from_lines_with_rules<Item, I>(
+        lines: I,
+        rules: &GameRules,
+    ) -> Result<Self, InvalidInputError>
+    where
+        Item: AsRef<str>,
+        I: Iterator<Item = Item>,
+    {
+        // Trailing whitespace (e.g. `\r` sent by bots running on Windows) is not a part of the map
+        let mut unlabelled_ships: Mask = 0;
+        let mut lettered_ships: Vec<(char, Mask)> = Vec::new();
+        let mut mines: Mask = 0;
+        let mut lines_count = 0;
+        for (y, line) in lines.take(usize::from(GAME_BOARD_SIZE)).enumerate() {
+            let line = line.as_ref().trim_end();
+            if line.len() != usize::from(GAME_BOARD_SIZE) {
+                return Err(InvalidInputError {});
+            }
+            for (x, label) in line.chars().enumerate() {
+                let cell: Mask = 1 << (y * usize::from(GAME_BOARD_SIZE) + x);
+                match label {
+                    '_' => (),
+                    '#' => unlabelled_ships |= cell,
+                    '*' => mines |= cell,
+                    label if cells::is_ship_label(label) => {
+                        match lettered_ships.iter_mut().find(|(other, _)| *other == label) {
+                            Some((_, ship)) => *ship |= cell,
+                            None => lettered_ships.push((label, cell)),
+                        }
+                    }
+                    _ => return Err(InvalidInputError {}),
+                }
+            }
+            lines_count += 1;
+        }
+        if lines_count != GAME_BOARD_SIZE || mines.count_ones() != u32::from(rules.mines) {
+            return Err(InvalidInputError {});
+        }
+
+        // Adjacent `#` cells form a single ship, and all the cells of a letter have to be
+        // connected since the same letter cannot mark several ships
+        let mut ships = Vec::new();
+        while unlabelled_ships != 0 {
+            let ship = bitboard::component(bitboard::lowest(unlabelled_ships), unlabelled_ships);
+            unlabelled_ships &= !ship;
+            ships.push(('#', ship));
+        }
+        for (label, ship) in lettered_ships {
+            if bitboard::component(bitboard::lowest(ship), ship) != ship {
+                return Err(InvalidInputError {});
+            }
+            ships.push((label, ship));
+        }
+        ships.sort_by_key(|&(_, ship)| ship.trailing_zeros());
+
+        // Validate amount of ships and their shape
+        let mut ships_count: Vec<u8> = rules
+            .fleet
+            .entries()
+            .iter()
+            .map(|entry| entry.count)
+            .collect();
+        for &(_, ship) in &ships {
+            let cells: Vec<Position> = bitboard::positions(ship).collect();
+            let fleet_entry_index = rules
+                .fleet
+                .find(&ShipShape::from_positions(&cells))
+                .ok_or(InvalidInputError {})?;
+            let ship_count = &mut ships_count[fleet_entry_index];
+            *ship_count = ship_count.checked_sub(1).ok_or(InvalidInputError {})?;
+        }
+        if ships_count.iter().sum::<u8>() > 0 {
+            return Err(InvalidInputError {});
+        }
+
+        // Validate how ships touch each other
+        let all_ships = ships
+            .iter()
+            .fold(0, |all_ships, &(_, ship)| all_ships | ship);
+        for &(_, ship) in &ships {
+            let forbidden = match rules.placement {
+                PlacementRule::NoContact => bitboard::dilate(ship),
+                PlacementRule::Corners => bitboard::dilate_orthogonally(ship),
+                PlacementRule::Any => 0,
+            };
+            if forbidden & all_ships & !ship != 0 {
+                return Err(InvalidInputError {});
+            }
+        }
+
+        Ok(Self { ships, mines })
+    }
+}
@@ -0,0 +1,78 @@
+#![feature(async_await, try_trait)]
+
+pub mod animation;
+pub mod bitboard;
+pub mod board;
+pub mod builtin;
+pub mod cells;
+pub mod codec;
+/// Boards and rules shared by the tests.
+#[cfg(test)]
+mod fixtures;
+pub mod fleet;
+pub mod game;
+pub mod game_log;
+pub mod heatmap;
+pub mod html;
+pub mod human;
+pub mod knowledge;
+pub mod layout;
+pub mod placement;
+pub mod placements;
+pub mod player;
+pub mod position;
+pub mod random;
+pub mod raster;
+pub mod rules;
+pub mod ship;
+pub mod similarity;
+pub mod simulate;
+pub mod snapshot;
+pub mod stats;
+pub mod terminal;
+
+pub const GAME_BOARD_SIZE: u8 = 10;
+
+#[derive(Debug, Copy, Clone)]
+pub struct InvalidInputError;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Draw,
+    Player1Win,
+    Player2Win,
+}
+
+impl GameResult {
+    pub fn from_results<T, E>(
+        player1_result: Result<T, E>,
+        player2_result: Result<T, E>,
+    ) -> Result<Self, (T, T)> {
+        match (player1_result, player2_result) {
+            (Ok(player1_value), Ok(player2_value)) => Err((player1_value, player2_value)),
+            (Err(_), Err(_)) => Ok(Self::Draw),
+            (Ok(_), Err(_)) => Ok(Self::Player1Win),
+            (Err(_), Ok(_)) => Ok(Self::Player2Win),
+        }
+    }
+
+    /// The verdict as it is printed at the end of the game log.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draw => "Draw!",
+            Self::Player1Win => "Player 1 won the game!",
+            Self::Player2Win => "Player 2 won the game!",
+        }
+    }
+
+    pub fn from_verdict(verdict: &str) -> Option<Self> {
+        [Self::Draw, Self::Player1Win, Self::Player2Win]
+            .iter()
+            .cloned()
+            .find(|game_result| game_result.as_str() == verdict)
+    }
+
+    pub fn print(self) {
+        println!("{}", self.as_str());
+    }
+}
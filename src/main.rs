@@ -1,119 +1,207 @@
 #![feature(async_await, try_trait)]
 
-mod board;
-mod cells;
-mod player;
-mod position;
-
-use board::GameBoardShotResult;
-use player::Player;
-
-const GAME_BOARD_SIZE: u8 = 10;
-
-#[derive(Debug, Copy, Clone)]
-pub struct InvalidInputError;
+use judge::board::{GameBoard, ShotReport};
+use judge::game::{Game, Turn};
+use judge::player::{OutputLimits, Player, StderrCapture};
+use judge::position::Position;
+use judge::random::Random;
+use judge::rules::{GameRules, RuleViolation};
+use judge::{
+    animation, game_log, heatmap, html, human, placements, similarity, simulate, snapshot, stats,
+    GameResult,
+};
 
 #[derive(structopt::StructOpt)]
+#[structopt(raw(setting = "structopt::clap::AppSettings::ArgRequiredElseHelp"))]
 struct Args {
-    player1: std::path::PathBuf,
-    player2: std::path::PathBuf,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+    /// The first player's bot (required unless a subcommand is given)
+    #[structopt(parse(from_os_str))]
+    player1: Option<std::path::PathBuf>,
+    /// The second player's bot (required unless a subcommand is given)
+    #[structopt(parse(from_os_str))]
+    player2: Option<std::path::PathBuf>,
+    /// Save stderr of the players into `player1.stderr` and `player2.stderr` files in this
+    /// directory instead of passing it through
+    #[structopt(long = "stderr-dir", parse(from_os_str))]
+    stderr_dir: Option<std::path::PathBuf>,
+    /// Maximum number of bytes of stderr to save per player
+    #[structopt(long = "stderr-limit", default_value = "65536")]
+    stderr_limit: usize,
+    #[structopt(flatten)]
+    output_limits: OutputLimits,
+    /// Seed of the random choices of the judge (e.g. the ship cells revealed for hitting mines)
+    /// to replay a game; a seed from the current time is used if not given (it is printed to
+    /// stderr either way)
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+    #[structopt(flatten)]
+    rules: GameRules,
 }
 
-#[derive(Debug, Copy, Clone)]
-enum GameResult {
-    Draw,
-    Player1Win,
-    Player2Win,
+#[derive(structopt::StructOpt)]
+enum Command {
+    #[structopt(name = "play-human")]
+    PlayHuman(human::PlayHumanArgs),
+    #[structopt(name = "render-html")]
+    RenderHtml(html::RenderHtmlArgs),
+    #[structopt(name = "render-board")]
+    RenderBoard(snapshot::RenderBoardArgs),
+    #[structopt(name = "render-gif")]
+    RenderGif(animation::RenderGifArgs),
+    #[structopt(name = "stats")]
+    Stats(stats::StatsArgs),
+    #[structopt(name = "heatmap")]
+    Heatmap(heatmap::HeatmapArgs),
+    #[structopt(name = "placements")]
+    Placements(placements::PlacementsArgs),
+    #[structopt(name = "similarity")]
+    Similarity(similarity::SimilarityArgs),
+    #[structopt(name = "simulate")]
+    Simulate(simulate::SimulateArgs),
 }
 
-impl GameResult {
-    fn from_results<T, E>(
-        player1_result: Result<T, E>,
-        player2_result: Result<T, E>,
-    ) -> Result<Self, (T, T)> {
-        match (player1_result, player2_result) {
-            (Ok(player1_value), Ok(player2_value)) => Err((player1_value, player2_value)),
-            (Err(_), Err(_)) => Ok(Self::Draw),
-            (Ok(_), Err(_)) => Ok(Self::Player1Win),
-            (Err(_), Ok(_)) => Ok(Self::Player2Win),
-        }
-    }
-
-    fn print(self) {
-        match self {
-            Self::Draw => {
-                println!("Draw!");
-            }
-            Self::Player1Win => {
-                println!("Player 1 won the game!");
-            }
-            Self::Player2Win => {
-                println!("Player 2 won the game!");
-            }
-        }
+impl Args {
+    fn stderr_capture(&self, player_name: &str) -> Option<StderrCapture> {
+        self.stderr_dir.as_ref().map(|stderr_dir| StderrCapture {
+            path: stderr_dir.join(format!("{}.stderr", player_name)),
+            limit: self.stderr_limit,
+        })
     }
 }
 
 #[paw::main]
 #[tokio::main]
 async fn main(args: Args) -> Result<(), Box<dyn std::error::Error>> {
-    let game_result = play(args).await;
-    game_result.print();
+    match args.command {
+        Some(Command::PlayHuman(play_human_args)) => {
+            human::play_human(play_human_args).await?.print()
+        }
+        Some(Command::RenderHtml(render_html_args)) => html::render_html(render_html_args)?,
+        Some(Command::RenderBoard(render_board_args)) => snapshot::render_board(render_board_args)?,
+        Some(Command::RenderGif(render_gif_args)) => animation::render_gif(render_gif_args)?,
+        Some(Command::Stats(stats_args)) => stats::stats(stats_args)?,
+        Some(Command::Heatmap(heatmap_args)) => heatmap::heatmap(heatmap_args)?,
+        Some(Command::Placements(placements_args)) => placements::placements(placements_args)?,
+        Some(Command::Similarity(similarity_args)) => {
+            similarity::similarity(similarity_args).await?
+        }
+        Some(Command::Simulate(simulate_args)) => simulate::simulate(simulate_args)?,
+        None => play(args).await?.print(),
+    }
     Ok(())
 }
 
-async fn play(args: Args) -> GameResult {
-    let player1 = Player::init(&args.player1).await;
-    let player2 = Player::init(&args.player2).await;
-
-    let (mut player1, mut player2) = match GameResult::from_results(player1, player2) {
-        Ok(game_result) => return game_result,
-        Err((player1, player2)) => (player1, player2),
+async fn play(args: Args) -> Result<GameResult, String> {
+    let (player1_exe, player2_exe) = match (&args.player1, &args.player2) {
+        (Some(player1_exe), Some(player2_exe)) => (player1_exe, player2_exe),
+        _ => return Err("paths to both players are required".to_owned()),
     };
+    args.rules.check()?;
+
+    if let Some(ref stderr_dir) = args.stderr_dir {
+        if let Err(err) = std::fs::create_dir_all(stderr_dir) {
+            eprintln!(
+                "Failed to create {} due to: {:?}",
+                stderr_dir.display(),
+                err
+            );
+        }
+    }
 
-    println!("{}", player1.map_mut().to_string());
-    println!("{}", player2.map_mut().to_string());
-
-    let game_result = start_battle(&mut player1, &mut player2).await;
-
-    eprintln!("Player 1 map: {:?}", player1.map_mut());
-    eprintln!("Player 2 map: {:?}", player2.map_mut());
-
-    game_result
+    let player1 = Player::init(
+        player1_exe,
+        args.stderr_capture("player1"),
+        args.output_limits,
+        &args.rules,
+    )
+    .await;
+    let player2 = Player::init(
+        player2_exe,
+        args.stderr_capture("player2"),
+        args.output_limits,
+        &args.rules,
+    )
+    .await;
+
+    let ((player1, player1_map), (player2, player2_map)) =
+        match GameResult::from_results(player1, player2) {
+            Ok(game_result) => return Ok(game_result),
+            Err(players) => players,
+        };
+
+    println!("{}", player1_map.to_string());
+    println!("{}", player2_map.to_string());
+
+    let seed = args.seed.unwrap_or_else(Random::seed_from_time);
+    eprintln!("Seed: {}", seed);
+    let mut game = Game::new(&args.rules, [player1_map, player2_map], Random::new(seed));
+    start_battle(&mut game, &mut [player1, player2]).await;
+
+    eprintln!("Player 1 map: {:?}", game.board(0));
+    eprintln!("Player 2 map: {:?}", game.board(1));
+
+    Ok(game.result().expect("the battle is over"))
 }
 
-async fn start_battle(player1: &mut Player, player2: &mut Player) -> GameResult {
-    loop {
-        loop {
-            if let Some(shot_position) = player1.next_shot_position().await {
-                let shot_result = player2.map_mut().shoot(shot_position);
-                player1.reply_shot_result(shot_result).await;
-                println!("1 {} {} {}", shot_position.x() + 1, shot_position.y() + 1, shot_result.as_str());
-                if let GameBoardShotResult::Miss = shot_result {
-                    break;
-                }
-                if player2.map_mut().hits_left() == 0 {
-                    return GameResult::Player1Win;
-                }
-            } else {
-                return GameResult::Player2Win;
+/// Plays the game to the end: asks the player whose turn it is for their shots, makes them and
+/// replies with their results.
+async fn start_battle(game: &mut Game<'_, GameBoard>, players: &mut [Player; 2]) {
+    while let Some(turn) = game.turn() {
+        let player = turn.player();
+        let shot_positions = match turn {
+            Turn::Shot { .. } => players[player]
+                .next_shot_position()
+                .await
+                .map(|shot_position| vec![shot_position]),
+            Turn::Volley { size, .. } => players[player].next_volley(size).await,
+        };
+        let shot_positions = match shot_positions {
+            Some(shot_positions) => shot_positions,
+            None => {
+                game.forfeit();
+                return;
+            }
+        };
+        let mut shot_reports = Vec::with_capacity(shot_positions.len());
+        for shot_position in shot_positions {
+            match shoot(game, shot_position) {
+                Ok(shot_report) => shot_reports.push(shot_report),
+                Err(_) => return,
             }
         }
-
-        loop {
-            if let Some(shot_position) = player2.next_shot_position().await {
-                let shot_result = player1.map_mut().shoot(shot_position);
-                player2.reply_shot_result(shot_result).await;
-                println!("2 {} {} {}", shot_position.x() + 1, shot_position.y() + 1, shot_result.as_str());
-                if let GameBoardShotResult::Miss = shot_result {
-                    break;
-                }
-                if player1.map_mut().hits_left() == 0 {
-                    return GameResult::Player2Win;
-                }
-            } else {
-                return GameResult::Player1Win;
+        match turn {
+            Turn::Shot { .. } => players[player].reply_shot_result(&shot_reports[0]).await,
+            Turn::Volley { .. } => {
+                players[player]
+                    .reply_volley_results(&shot_reports, game.rules())
+                    .await
+            }
+        }
+        for shot_report in &shot_reports {
+            if let Some(revealed_cell) = shot_report.revealed_cell {
+                players[1 - player].send_revealed_cell(revealed_cell).await;
             }
         }
     }
+    if game.is_adjudicated() {
+        let mut note = String::new();
+        game_log::log_adjudication(&mut note, game);
+        print!("{}", note);
+    }
+}
+
+/// Makes the next shot of the player in the game and prints it into the log.
+fn shoot(
+    game: &mut Game<'_, GameBoard>,
+    shot_position: Position,
+) -> Result<ShotReport, RuleViolation> {
+    let mut line = String::new();
+    let shot = game_log::log_shot(&mut line, game, shot_position);
+    print!("{}", line);
+    if let Err(violation) = shot.outcome {
+        eprintln!("Player {} violated the rules: {:?}", shot.player_number, violation);
+    }
+    shot.outcome
 }
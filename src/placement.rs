@@ -0,0 +1,284 @@
+use crate::board::{Board, GameBoard};
+use crate::fleet::{Fleet, ShipShape};
+use crate::position::Position;
+use crate::random::Random;
+use crate::rules::GameRules;
+use crate::GAME_BOARD_SIZE;
+
+/// Ships and mines placed on a board so far, each one checked by the rules of the judge as it is
+/// placed, and the ships of the fleet that are still to be placed. The mines are placed once all
+/// the ships are.
+pub struct Placement<'a> {
+    rules: &'a GameRules,
+    pending_ships: Vec<ShipShape>,
+    placed_ships: Vec<Vec<Position>>,
+    placed_shapes: Vec<ShipShape>,
+    mines: Vec<Position>,
+}
+
+impl<'a> Placement<'a> {
+    pub fn new(rules: &'a GameRules) -> Self {
+        Self {
+            rules,
+            pending_ships: rules.fleet.ships(),
+            placed_ships: Vec::new(),
+            placed_shapes: Vec::new(),
+            mines: Vec::new(),
+        }
+    }
+
+    /// The shape of the ship to place next.
+    pub fn next_ship(&self) -> Option<&ShipShape> {
+        self.pending_ships.first()
+    }
+
+    pub fn placed_ships(&self) -> &[Vec<Position>] {
+        &self.placed_ships
+    }
+
+    pub fn mines(&self) -> &[Position] {
+        &self.mines
+    }
+
+    /// Number of the mines to place once all the ships are placed.
+    pub fn pending_mines(&self) -> usize {
+        usize::from(self.rules.mines).saturating_sub(self.mines.len())
+    }
+
+    /// Draws the map the same way players send it, marking each ship with its own letter, so the
+    /// ships are told apart even if they touch each other, and the mines with `*`.
+    fn lines(ships: &[Vec<Position>], mines: &[Position]) -> Vec<String> {
+        // `O` and `X` mark shot cells, so they cannot label ships
+        const LABELS: &[u8] = b"ABCDEFGHIJKLMNPQRSTUVWYZabcdefghijklmnopqrstuvwxyz";
+
+        let mut cells = vec!['_'; usize::from(GAME_BOARD_SIZE * GAME_BOARD_SIZE)];
+        for (ship_index, ship) in ships.iter().enumerate() {
+            let label = LABELS
+                .get(ship_index)
+                .map_or('#', |&label| char::from(label));
+            for position in ship {
+                cells[usize::from(position.yx())] = label;
+            }
+        }
+        for mine in mines {
+            cells[usize::from(mine.yx())] = '*';
+        }
+        cells
+            .chunks(usize::from(GAME_BOARD_SIZE))
+            .map(|row| row.iter().collect())
+            .collect()
+    }
+
+    /// Checks whether the map with the ship added to the already placed ones is accepted by the
+    /// judge with the fleet limited to the placed ships.
+    pub fn accepts(&self, cells: &[Position], shape: &ShipShape) -> bool {
+        let mut placed_ships = self.placed_ships.clone();
+        placed_ships.push(cells.to_vec());
+        let mut placed_shapes = self.placed_shapes.clone();
+        placed_shapes.push(shape.clone());
+        let rules = GameRules {
+            fleet: Fleet::from_shapes(placed_shapes),
+            mines: 0,
+            ..self.rules.clone()
+        };
+        GameBoard::from_lines_with_rules(Self::lines(&placed_ships, &[]).iter(), &rules).is_ok()
+    }
+
+    /// Places the next ship at the cells if the judge accepts that.
+    pub fn try_place(&mut self, cells: Vec<Position>, shape: ShipShape) -> bool {
+        if !self.accepts(&cells, &shape) {
+            return false;
+        }
+        self.placed_ships.push(cells);
+        self.placed_shapes.push(shape);
+        self.pending_ships.remove(0);
+        true
+    }
+
+    /// Checks whether neither a ship nor a mine is placed at the cell.
+    pub fn is_empty(&self, position: Position) -> bool {
+        !self.mines.contains(&position)
+            && !self
+                .placed_ships
+                .iter()
+                .flatten()
+                .any(|&cell| cell == position)
+    }
+
+    /// Places the next mine at the cell if all the ships are placed and the cell is empty.
+    pub fn try_place_mine(&mut self, position: Position) -> bool {
+        if !self.pending_ships.is_empty() || self.pending_mines() == 0 || !self.is_empty(position) {
+            return false;
+        }
+        self.mines.push(position);
+        true
+    }
+
+    /// Removes the mine placed last, or the ship placed last if there are no mines, so it is the
+    /// next one to place again.
+    pub fn undo(&mut self) {
+        if self.mines.pop().is_some() {
+            return;
+        }
+        if let (Some(_), Some(shape)) = (self.placed_ships.pop(), self.placed_shapes.pop()) {
+            self.pending_ships.insert(0, shape);
+        }
+    }
+
+    /// Places the rest of the ships and then the rest of the mines at random positions, starting
+    /// over a few times if the ships placed first leave no room for the others.
+    pub fn auto_place(&mut self, random: &mut Random) -> bool {
+        self.auto_place_ships(random) && self.auto_place_mines(random)
+    }
+
+    fn auto_place_ships(&mut self, random: &mut Random) -> bool {
+        const RESTARTS: usize = 100;
+        const ATTEMPTS_PER_SHIP: usize = 1000;
+
+        let placed_ships_count = self.placed_ships.len();
+        for _ in 0..RESTARTS {
+            while let Some(shape) = self.pending_ships.first().cloned() {
+                let variants = shape.variants();
+                let is_placed = (0..ATTEMPTS_PER_SHIP).any(|_| {
+                    let variant = &variants[random.below(variants.len())];
+                    let top_left = Position::top_left()
+                        .iter()
+                        .nth(random.below(usize::from(GAME_BOARD_SIZE * GAME_BOARD_SIZE)))
+                        .unwrap();
+                    match variant.place_at(top_left) {
+                        Some(cells) => self.try_place(cells, variant.clone()),
+                        None => false,
+                    }
+                });
+                if !is_placed {
+                    break;
+                }
+            }
+            if self.pending_ships.is_empty() {
+                return true;
+            }
+            while self.placed_ships.len() > placed_ships_count {
+                self.undo();
+            }
+        }
+        false
+    }
+
+    fn auto_place_mines(&mut self, random: &mut Random) -> bool {
+        let mut empty_cells: Vec<Position> = Position::top_left()
+            .iter()
+            .filter(|&position| self.is_empty(position))
+            .collect();
+        while self.pending_mines() > 0 {
+            if empty_cells.is_empty() {
+                return false;
+            }
+            let position = empty_cells.swap_remove(random.below(empty_cells.len()));
+            self.mines.push(position);
+        }
+        true
+    }
+
+    /// Returns the board once all the ships and mines are placed.
+    pub fn board<B: Board>(&self) -> Option<B> {
+        if !self.pending_ships.is_empty() || self.pending_mines() > 0 {
+            return None;
+        }
+        let lines = Self::lines(&self.placed_ships, &self.mines);
+        B::from_lines_with_rules(lines.iter(), self.rules).ok()
+    }
+}
+
+/// Places all the ships of the fleet and the mines of the rules at random positions, the same way
+/// for the same state of the `random` generator.
+pub fn random_board<B: Board>(rules: &GameRules, random: &mut Random) -> Option<B> {
+    let mut placement = Placement::new(rules);
+    if !placement.auto_place(random) {
+        return None;
+    }
+    placement.board()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_Placement_try_place() {
+        let rules = GameRules::default();
+        let mut placement = Placement::new(&rules);
+        let ship = |s: &str| Position::parse_list(s).unwrap();
+        let shape = |s: &str| s.parse::<ShipShape>().unwrap();
+        assert_eq!(placement.next_ship().map(ShipShape::size), Some(4));
+        assert!(placement.try_place(ship("8 1 8 2 8 3 8 4"), shape("#/#/#/#")));
+        assert_eq!(placement.next_ship().map(ShipShape::size), Some(3));
+
+        // Ships cannot touch each other
+        assert!(!placement.accepts(&ship("5 1 6 1 7 1"), &shape("###")));
+        assert!(!placement.try_place(ship("5 1 6 1 7 1"), shape("###")));
+        assert!(placement.try_place(ship("1 1 2 1 3 1"), shape("###")));
+        assert_eq!(
+            Placement::lines(placement.placed_ships(), &[])[..2].to_vec(),
+            vec!["BBB____A__", "_______A__"]
+        );
+
+        placement.undo();
+        assert_eq!(placement.placed_ships().len(), 1);
+        assert_eq!(placement.pending_ships.len(), 9);
+        assert!(placement.board::<GameBoard>().is_none());
+    }
+
+    #[test]
+    fn test_Placement_auto_place() {
+        for seed in 0..10 {
+            let rules = GameRules::default();
+            let mut placement = Placement::new(&rules);
+            let ship = Position::parse_list("1 1 2 1 3 1 4 1").unwrap();
+            assert!(placement.try_place(ship, "####".parse().unwrap()));
+            assert!(placement.auto_place(&mut Random::new(seed)));
+            assert_eq!(placement.placed_ships().len(), 10);
+            assert!(placement.board::<GameBoard>().is_some());
+        }
+
+        let rules = GameRules {
+            fleet: "1x##/##,2x#_/#_/##,1x###/_#_,2x#".parse().unwrap(),
+            ..GameRules::default()
+        };
+        let mut placement = Placement::new(&rules);
+        assert!(placement.auto_place(&mut Random::new(0)));
+        assert!(placement.board::<GameBoard>().is_some());
+    }
+
+    #[test]
+    fn test_Placement_try_place_mine() {
+        let rules = GameRules {
+            fleet: "2x#".parse().unwrap(),
+            mines: 2,
+            ..GameRules::default()
+        };
+        let mut placement = Placement::new(&rules);
+        let position = |s: &str| s.parse::<Position>().unwrap();
+        let shape: ShipShape = "#".parse().unwrap();
+
+        // Mines are placed once all the ships are
+        assert!(!placement.try_place_mine(position("5 5")));
+        assert!(placement.try_place(vec![position("1 1")], shape.clone()));
+        assert!(placement.try_place(vec![position("3 1")], shape));
+        assert!(!placement.try_place_mine(position("1 1")));
+        assert!(placement.try_place_mine(position("2 1")));
+        assert!(!placement.try_place_mine(position("2 1")));
+        assert_eq!(placement.pending_mines(), 1);
+        assert!(placement.board::<GameBoard>().is_none());
+
+        assert!(placement.auto_place(&mut Random::new(0)));
+        assert_eq!(placement.mines().len(), 2);
+        let board: GameBoard = placement.board().unwrap();
+        assert!(board.get(position("2 1")).is_mine());
+
+        placement.undo();
+        assert_eq!(placement.pending_mines(), 1);
+        assert_eq!(placement.placed_ships().len(), 2);
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt::Write as _;
+
+use crate::bitboard::{self, Mask};
+use crate::board::GameBoard;
+use crate::position::{Position, Symmetry};
+use crate::rules::GameRules;
+use crate::stats::{self, format_table};
+
+/// Find bots which reuse the same fleet placement and boards shared between different bots over
+/// a directory of game logs
+///
+/// The bots are identified by the names of the log files, as for the `stats` command.
+#[derive(Debug, structopt::StructOpt)]
+pub struct PlacementsArgs {
+    #[structopt(parse(from_os_str))]
+    dir: std::path::PathBuf,
+    /// Boards of different bots are reported as similar if they differ in at most this many cells
+    /// (after rotating or mirroring one of them)
+    #[structopt(long = "max-diff", default_value = "4")]
+    max_diff: u32,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub fn placements(args: PlacementsArgs) -> Result<(), String> {
+    let mut analysis = PlacementAnalysis::default();
+    for (names, game_log) in stats::read_logs(&args.dir, &args.rules)? {
+        if let Some(ref boards) = game_log.boards {
+            for (name, board) in names.iter().zip(boards) {
+                analysis.add(name, board);
+            }
+        }
+    }
+    if analysis.bots.is_empty() {
+        return Err("no boards of the bots are found in the logs".to_owned());
+    }
+    print!("{}", analysis.report(args.max_diff));
+    Ok(())
+}
+
+/// The ship cells of the board.
+pub fn ship_mask(board: &GameBoard) -> Mask {
+    Position::top_left()
+        .iter()
+        .filter(|&position| board.get(position).is_ship())
+        .fold(0, |mask, position| mask | bitboard::bit(position))
+}
+
+/// The same mask for all the boards which are rotations or reflections of each other.
+pub fn canonical_mask(board: &GameBoard) -> Mask {
+    Symmetry::ALL
+        .iter()
+        .map(|&symmetry| ship_mask(&board.transform(symmetry)))
+        .min()
+        .unwrap_or(0)
+}
+
+/// How many cells differ between the boards, if the second one is rotated or mirrored the way
+/// that makes them closest.
+pub fn difference(mask: Mask, other: Mask) -> u32 {
+    Symmetry::ALL
+        .iter()
+        .map(|&symmetry| (mask ^ bitboard::transform(other, symmetry)).count_ones())
+        .min()
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone)]
+pub struct BotPlacements {
+    pub name: String,
+    /// The boards of every game in the order they were met.
+    pub boards: Vec<Mask>,
+    /// The canonical masks of the `boards`.
+    pub canonical_boards: Vec<Mask>,
+}
+
+impl BotPlacements {
+    /// The number of different boards.
+    pub fn distinct(&self) -> usize {
+        let mut boards = self.boards.clone();
+        boards.sort();
+        boards.dedup();
+        boards.len()
+    }
+
+    /// The number of different boards if the boards which are rotations or reflections of each
+    /// other are counted as the same one.
+    pub fn distinct_up_to_symmetry(&self) -> usize {
+        let mut boards = self.canonical_boards.clone();
+        boards.sort();
+        boards.dedup();
+        boards.len()
+    }
+
+    /// How many times the most frequent board (up to symmetry) was used.
+    pub fn most_repeated(&self) -> usize {
+        let mut boards = self.canonical_boards.clone();
+        boards.sort();
+        let mut most_repeated = 0;
+        let mut start = 0;
+        for end in 1..=boards.len() {
+            if end == boards.len() || boards[end] != boards[start] {
+                most_repeated = std::cmp::max(most_repeated, end - start);
+                start = end;
+            }
+        }
+        most_repeated
+    }
+}
+
+/// Boards of two different bots which differ in a few cells only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarBoards {
+    pub bots: [String; 2],
+    /// The number of pairs of distinct boards of the bots which are similar.
+    pub pairs: usize,
+    /// The least number of different cells between the boards of the bots.
+    pub min_difference: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct PlacementAnalysis {
+    pub bots: Vec<BotPlacements>,
+}
+
+impl PlacementAnalysis {
+    pub fn add(&mut self, name: &str, board: &GameBoard) {
+        let (mask, canonical) = (ship_mask(board), canonical_mask(board));
+        match self.bots.iter_mut().find(|bot| bot.name == name) {
+            Some(bot) => {
+                bot.boards.push(mask);
+                bot.canonical_boards.push(canonical);
+            }
+            None => self.bots.push(BotPlacements {
+                name: name.to_owned(),
+                boards: vec![mask],
+                canonical_boards: vec![canonical],
+            }),
+        }
+    }
+
+    /// The pairs of bots with boards which differ in at most `max_difference` cells.
+    pub fn similar_boards(&self, max_difference: u32) -> Vec<SimilarBoards> {
+        let distinct_boards: Vec<Vec<Mask>> = self
+            .bots
+            .iter()
+            .map(|bot| {
+                let mut boards = bot.boards.clone();
+                boards.sort();
+                boards.dedup();
+                boards
+            })
+            .collect();
+        let mut similar = Vec::new();
+        for (index, bot) in self.bots.iter().enumerate() {
+            for (other_index, other_bot) in self.bots.iter().enumerate().skip(index + 1) {
+                let mut pairs = 0;
+                let mut min_difference = None;
+                for &board in &distinct_boards[index] {
+                    for &other_board in &distinct_boards[other_index] {
+                        let difference = difference(board, other_board);
+                        if difference <= max_difference {
+                            pairs += 1;
+                            min_difference = Some(std::cmp::min(
+                                difference,
+                                min_difference.unwrap_or(difference),
+                            ));
+                        }
+                    }
+                }
+                if let Some(min_difference) = min_difference {
+                    similar.push(SimilarBoards {
+                        bots: [bot.name.clone(), other_bot.name.clone()],
+                        pairs,
+                        min_difference,
+                    });
+                }
+            }
+        }
+        similar.sort_by_key(|similar| similar.min_difference);
+        similar
+    }
+
+    pub fn report(&self, max_difference: u32) -> String {
+        let mut rows = vec![vec![
+            "Bot".to_owned(),
+            "Boards".to_owned(),
+            "Distinct".to_owned(),
+            "Up to symmetry".to_owned(),
+            "Most repeated".to_owned(),
+        ]];
+        for bot in &self.bots {
+            let most_repeated = bot.most_repeated();
+            rows.push(vec![
+                bot.name.clone(),
+                bot.boards.len().to_string(),
+                bot.distinct().to_string(),
+                bot.distinct_up_to_symmetry().to_string(),
+                format!(
+                    "{} ({:.0}%)",
+                    most_repeated,
+                    most_repeated as f64 * 100.0 / bot.boards.len() as f64
+                ),
+            ]);
+        }
+        let mut report = format_table(&rows);
+
+        let predictable: Vec<&str> = self
+            .bots
+            .iter()
+            .filter(|bot| bot.most_repeated() > 1)
+            .map(|bot| bot.name.as_str())
+            .collect();
+        if !predictable.is_empty() {
+            let _ = writeln!(
+                report,
+                "\nReused boards (the same or rotated/mirrored): {}",
+                predictable.join(", ")
+            );
+        }
+
+        let similar = self.similar_boards(max_difference);
+        if similar.is_empty() {
+            let _ = writeln!(
+                report,
+                "\nNo boards of different bots differ in {} cells or less",
+                max_difference
+            );
+        } else {
+            let _ = writeln!(
+                report,
+                "\nSimilar boards of different bots (at most {} different cells):",
+                max_difference
+            );
+            for similar in &similar {
+                let closest = if similar.min_difference == 0 {
+                    "identical up to symmetry".to_owned()
+                } else {
+                    format!("differ in {} cells", similar.min_difference)
+                };
+                let _ = writeln!(
+                    report,
+                    "{} and {}: {} similar pair(s) of boards, the closest ones {}",
+                    similar.bots[0], similar.bots[1], similar.pairs, closest
+                );
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures::{BOARD, OTHER_BOARD};
+
+    fn parse_board(lines: &str) -> GameBoard {
+        GameBoard::from_lines(lines.split('\n')).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_mask() {
+        let board = parse_board(BOARD);
+        let mask = ship_mask(&board);
+        assert_eq!(mask.count_ones(), 20);
+        for &symmetry in &Symmetry::ALL {
+            let transformed = board.transform(symmetry);
+            assert_eq!(bitboard::transform(mask, symmetry), ship_mask(&transformed));
+            assert_eq!(canonical_mask(&transformed), canonical_mask(&board));
+            assert_eq!(difference(ship_mask(&transformed), mask), 0);
+        }
+    }
+
+    #[test]
+    fn test_PlacementAnalysis() {
+        let moved = BOARD.replace("#_#_#_#___\n", "#_#_#___#_\n");
+        let other = OTHER_BOARD;
+        let mut analysis = PlacementAnalysis::default();
+        analysis.add("alpha", &parse_board(BOARD));
+        analysis.add("alpha", &parse_board(BOARD).transform(Symmetry::Rotate90));
+        analysis.add("alpha", &parse_board(BOARD));
+        analysis.add("beta", &parse_board(&moved));
+        analysis.add("gamma", &parse_board(other));
+        analysis.add("gamma", &parse_board(other));
+
+        let alpha = &analysis.bots[0];
+        assert_eq!((alpha.distinct(), alpha.distinct_up_to_symmetry()), (2, 1));
+        assert_eq!(alpha.most_repeated(), 3);
+        assert_eq!(analysis.bots[1].most_repeated(), 1);
+
+        assert_eq!(
+            analysis.similar_boards(2),
+            [SimilarBoards {
+                bots: ["alpha".to_owned(), "beta".to_owned()],
+                pairs: 2,
+                min_difference: 2,
+            }]
+        );
+        assert_eq!(analysis.similar_boards(1), []);
+
+        let report = analysis.report(2);
+        assert!(report.contains("\nalpha       3         2               1       3 (100%)\n"));
+        assert!(report.contains("Reused boards (the same or rotated/mirrored): alpha, gamma"));
+        assert!(report
+            .contains("alpha and beta: 2 similar pair(s) of boards, the closest ones differ in 2"));
+    }
+}
@@ -2,27 +2,52 @@ use futures_util::sink::SinkExt;
 use futures_util::stream::StreamExt;
 use std::process::Stdio;
 use tokio::codec::{FramedRead, FramedWrite, LinesCodec};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio_process::Command;
 
-use crate::board::{GameBoard, GameBoardShotResult};
+use crate::board::{GameBoard, ShotReport};
+use crate::codec::BoundedLinesCodec;
 use crate::position::Position;
+use crate::rules::GameRules;
 use crate::InvalidInputError;
 
+/// Where to save the player's stderr and how many bytes of it to keep.
+#[derive(Debug, Clone)]
+pub struct StderrCapture {
+    pub path: std::path::PathBuf,
+    pub limit: usize,
+}
+
+/// Limits on the player's output; exceeding any of them is a violation of the protocol.
+#[derive(Debug, Copy, Clone, structopt::StructOpt)]
+pub struct OutputLimits {
+    /// Maximum length of a single line a player is allowed to output
+    #[structopt(long = "max-line-length", default_value = "1024")]
+    pub max_line_length: usize,
+    /// Maximum number of bytes a player is allowed to output during the game
+    #[structopt(long = "max-output-bytes", default_value = "1048576")]
+    pub max_output_bytes: usize,
+}
+
 pub struct Player {
-    reader: FramedRead<tokio_process::ChildStdout, LinesCodec>,
+    reader: FramedRead<tokio_process::ChildStdout, BoundedLinesCodec>,
     writer: FramedWrite<tokio_process::ChildStdin, LinesCodec>,
-    map: GameBoard,
 }
 
 impl Player {
-    pub fn map_mut(&mut self) -> &mut GameBoard {
-        &mut self.map
-    }
-
-    pub async fn init(player_exe: &std::path::Path) -> Result<Self, InvalidInputError> {
+    /// Starts the player's bot and reads the map of its ships.
+    pub async fn init(
+        player_exe: &std::path::Path,
+        stderr_capture: Option<StderrCapture>,
+        output_limits: OutputLimits,
+        rules: &GameRules,
+    ) -> Result<(Self, GameBoard), InvalidInputError> {
         let mut player_cmd = Command::new(player_exe);
         player_cmd.stdin(Stdio::piped());
         player_cmd.stdout(Stdio::piped());
+        if stderr_capture.is_some() {
+            player_cmd.stderr(Stdio::piped());
+        }
 
         let mut child = player_cmd.spawn().expect("failed to spawn command");
 
@@ -35,7 +60,21 @@ impl Player {
             .take()
             .expect("child did not have a handle to stdout");
 
-        let mut reader = FramedRead::new(player_stdout, LinesCodec::new());
+        if let Some(stderr_capture) = stderr_capture {
+            let player_stderr = child
+                .stderr()
+                .take()
+                .expect("child did not have a handle to stderr");
+            tokio::spawn(capture_stderr(player_stderr, stderr_capture));
+        }
+
+        let mut reader = FramedRead::new(
+            player_stdout,
+            BoundedLinesCodec::new(
+                output_limits.max_line_length,
+                output_limits.max_output_bytes,
+            ),
+        );
         let writer = FramedWrite::new(player_stdin, LinesCodec::new());
 
         // make progress on its own while we await for any output.
@@ -45,12 +84,8 @@ impl Player {
             eprintln!("child status was: {}", status);
         });
 
-        let map = GameBoard::read(&mut reader).await?;
-        Ok(Self {
-            reader,
-            writer,
-            map,
-        })
+        let map = GameBoard::read(&mut reader, rules).await?;
+        Ok((Self { reader, writer }, map))
     }
 
     pub async fn next_shot_position(&mut self) -> Option<Position> {
@@ -69,9 +104,106 @@ impl Player {
         }
     }
 
-    pub async fn reply_shot_result(&mut self, shot_result: GameBoardShotResult) {
+    pub async fn reply_shot_result(&mut self, shot_report: &ShotReport) {
         let _ = self.writer
-            .send(shot_result.as_str().to_owned())
+            .send(shot_report.to_string())
             .await;
     }
+
+    /// Tells the player that the opponent hit a mine, so one of the player's ship cells is
+    /// revealed to the opponent (`reveal x y`).
+    pub async fn send_revealed_cell(&mut self, position: Position) {
+        let _ = self.writer.send(format!("reveal {}", position)).await;
+    }
+
+    /// Asks the player for a volley of exactly `volley_size` shots (`salvo <volley_size>`), which
+    /// the player sends as a single line of `x y` pairs.
+    pub async fn next_volley(&mut self, volley_size: u8) -> Option<Vec<Position>> {
+        let _ = self.writer.send(format!("salvo {}", volley_size)).await;
+        match self.reader.next().await? {
+            Ok(line) => match Position::parse_list(&line) {
+                Ok(ref positions) if positions.len() != usize::from(volley_size) => {
+                    eprintln!(
+                        "Next volley failed due to: expected {} shots, got {}",
+                        volley_size,
+                        positions.len()
+                    );
+                    None
+                }
+                Ok(positions) => Some(positions),
+                Err(err) => {
+                    eprintln!("Next volley failed due to: {:?}", err);
+                    None
+                }
+            },
+            Err(err) => {
+                eprintln!("Next volley failed due to: {:?}", err);
+                None
+            }
+        }
+    }
+
+    /// Replies with the results of all the shots of the volley on a single line (`miss hit sunk`),
+    /// followed by a separate line with the full report (`sunk 1 1 2 1` or `mine 5 5`) of every
+    /// shot whose report carries cells by the rules, in the order of the shots.
+    pub async fn reply_volley_results(&mut self, shot_reports: &[ShotReport], rules: &GameRules) {
+        let results: Vec<&str> = shot_reports
+            .iter()
+            .map(|shot_report| shot_report.result.as_str())
+            .collect();
+        let _ = self.writer.send(results.join(" ")).await;
+        for shot_report in shot_reports {
+            if rules.reveals_cells(shot_report.result) {
+                let _ = self.writer.send(shot_report.to_string()).await;
+            }
+        }
+    }
+}
+
+async fn capture_stderr(
+    mut player_stderr: tokio_process::ChildStderr,
+    stderr_capture: StderrCapture,
+) {
+    let mut file = match tokio::fs::File::create(stderr_capture.path.clone()).await {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!(
+                "Failed to create {} due to: {:?}",
+                stderr_capture.path.display(),
+                err
+            );
+            None
+        }
+    };
+    let mut bytes_left = stderr_capture.limit;
+    let mut buf = [0; 4096];
+    // Keep draining the pipe even after the limit is reached (or the file is unavailable), so
+    // the player does not get blocked on writing into a full pipe.
+    loop {
+        let len = match player_stderr.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => len,
+        };
+        if let Some(ref mut output) = file {
+            let captured_len = std::cmp::min(len, bytes_left);
+            bytes_left -= captured_len;
+            let mut write_result = output.write_all(&buf[..captured_len]).await;
+            if write_result.is_ok() && captured_len < len {
+                write_result = output
+                    .write_all(
+                        format!("\n[truncated after {} bytes]\n", stderr_capture.limit).as_bytes(),
+                    )
+                    .await;
+                file = None;
+            }
+            if let Err(err) = write_result {
+                eprintln!(
+                    "Failed to write {} due to: {:?}",
+                    stderr_capture.path.display(),
+                    err
+                );
+                file = None;
+            }
+        }
+    }
 }
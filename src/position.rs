@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use crate::{InvalidInputError, GAME_BOARD_SIZE};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Position {
     yx: u8,
 }
@@ -18,6 +18,17 @@ impl Position {
         }
     }
 
+    /// Creates a position from 0-based coordinates unless they are out of the board.
+    pub fn from_xy(x: u8, y: u8) -> Option<Self> {
+        if x < GAME_BOARD_SIZE && y < GAME_BOARD_SIZE {
+            Some(Self {
+                yx: y * GAME_BOARD_SIZE + x,
+            })
+        } else {
+            None
+        }
+    }
+
     pub fn x(self) -> u8 {
         self.yx % GAME_BOARD_SIZE
     }
@@ -42,6 +53,21 @@ impl Position {
         self.iter_above().nth(1)
     }
 
+    pub fn get_below(self) -> Option<Self> {
+        self.iter_below().nth(1)
+    }
+
+    /// Returns all the adjacent positions including the diagonal ones.
+    pub fn neighbours(self) -> Vec<Self> {
+        [self.get_above(), Some(self), self.get_below()]
+            .iter()
+            .flatten()
+            .flat_map(|&pos| vec![pos.get_left(), Some(pos), pos.get_right()])
+            .flatten()
+            .filter(|&pos| pos != self)
+            .collect()
+    }
+
     pub fn iter(self) -> PositionIter {
         PositionIter {
             start: self,
@@ -72,14 +98,9 @@ impl Position {
     pub fn iter_below(self) -> impl Iterator<Item = Position> {
         self.iter().step_by(usize::from(GAME_BOARD_SIZE))
     }
-}
-
-impl std::str::FromStr for Position {
-    type Err = InvalidInputError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<&str> = s.split_whitespace().collect();
 
+    /// Parses 1-based `x` and `y` coordinates as they are sent by players.
+    pub fn from_coords(x: &str, y: &str) -> Result<Self, InvalidInputError> {
         let parse = |value: &str| {
             value
                 .parse()
@@ -93,9 +114,136 @@ impl std::str::FromStr for Position {
                 })
         };
         Ok(Position {
-            yx: (parse(coords[1])? - 1) * GAME_BOARD_SIZE + parse(coords[0])? - 1,
+            yx: (parse(y)? - 1) * GAME_BOARD_SIZE + parse(x)? - 1,
         })
     }
+
+    /// Parses a whitespace-separated list of `x y` coordinates, e.g. `1 1 10 10`.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, InvalidInputError> {
+        let coords: Vec<&str> = s.split_whitespace().collect();
+        let coords = coords.chunks_exact(2);
+        if !coords.remainder().is_empty() {
+            return Err(InvalidInputError {});
+        }
+        coords
+            .map(|coords| Self::from_coords(coords[0], coords[1]))
+            .collect()
+    }
+
+    /// Rotates the position around the center of the board by 90 degrees clockwise.
+    pub fn rotate_90(self) -> Self {
+        Self::from_transformed_xy(GAME_BOARD_SIZE - 1 - self.y(), self.x())
+    }
+
+    pub fn rotate_180(self) -> Self {
+        Self::from_transformed_xy(
+            GAME_BOARD_SIZE - 1 - self.x(),
+            GAME_BOARD_SIZE - 1 - self.y(),
+        )
+    }
+
+    pub fn rotate_270(self) -> Self {
+        Self::from_transformed_xy(self.y(), GAME_BOARD_SIZE - 1 - self.x())
+    }
+
+    /// Mirrors the position from left to right.
+    pub fn reflect_horizontally(self) -> Self {
+        Self::from_transformed_xy(GAME_BOARD_SIZE - 1 - self.x(), self.y())
+    }
+
+    /// Mirrors the position from top to bottom.
+    pub fn reflect_vertically(self) -> Self {
+        Self::from_transformed_xy(self.x(), GAME_BOARD_SIZE - 1 - self.y())
+    }
+
+    /// Mirrors the position across the diagonal from the top left to the bottom right corner.
+    pub fn reflect_diagonally(self) -> Self {
+        Self::from_transformed_xy(self.y(), self.x())
+    }
+
+    /// Mirrors the position across the diagonal from the top right to the bottom left corner.
+    pub fn reflect_anti_diagonally(self) -> Self {
+        Self::from_transformed_xy(
+            GAME_BOARD_SIZE - 1 - self.y(),
+            GAME_BOARD_SIZE - 1 - self.x(),
+        )
+    }
+
+    pub fn transform(self, symmetry: Symmetry) -> Self {
+        match symmetry {
+            Symmetry::Identity => self,
+            Symmetry::Rotate90 => self.rotate_90(),
+            Symmetry::Rotate180 => self.rotate_180(),
+            Symmetry::Rotate270 => self.rotate_270(),
+            Symmetry::ReflectHorizontally => self.reflect_horizontally(),
+            Symmetry::ReflectVertically => self.reflect_vertically(),
+            Symmetry::ReflectDiagonally => self.reflect_diagonally(),
+            Symmetry::ReflectAntiDiagonally => self.reflect_anti_diagonally(),
+        }
+    }
+
+    /// The coordinates of a transformed position are always on the board.
+    fn from_transformed_xy(x: u8, y: u8) -> Self {
+        Self {
+            yx: y * GAME_BOARD_SIZE + x,
+        }
+    }
+}
+
+/// One of the 8 symmetries of the square board (rotations and reflections), which map every
+/// position on the board to a position on the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    /// Rotation by 90 degrees clockwise.
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    ReflectHorizontally,
+    ReflectVertically,
+    ReflectDiagonally,
+    ReflectAntiDiagonally,
+}
+
+impl Symmetry {
+    pub const ALL: [Self; 8] = [
+        Self::Identity,
+        Self::Rotate90,
+        Self::Rotate180,
+        Self::Rotate270,
+        Self::ReflectHorizontally,
+        Self::ReflectVertically,
+        Self::ReflectDiagonally,
+        Self::ReflectAntiDiagonally,
+    ];
+
+    /// The symmetry which undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Rotate90 => Self::Rotate270,
+            Self::Rotate270 => Self::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+}
+
+impl std::fmt::Display for Position {
+    /// Formats the position as 1-based `x y` coordinates the same way players send them.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.x() + 1, self.y() + 1)
+    }
+}
+
+impl std::str::FromStr for Position {
+    type Err = InvalidInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut coords = s.split_whitespace();
+        match (coords.next(), coords.next(), coords.next()) {
+            (Some(x), Some(y), None) => Self::from_coords(x, y),
+            _ => Err(InvalidInputError {}),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -238,4 +386,111 @@ mod tests {
         let pos = Position::top_left().iter().nth(1).unwrap();
         assert_eq!(pos.get_left().map(|x| x.yx()), Some(0));
     }
+
+    #[test]
+    fn test_Position_from_str() {
+        let pos: Position = "1 1".parse().unwrap();
+        assert_eq!(pos.yx(), 0);
+        let pos: Position = "10 1\r".parse().unwrap();
+        assert_eq!((pos.x(), pos.y()), (9, 0));
+        let pos: Position = "  3   7  ".parse().unwrap();
+        assert_eq!((pos.x(), pos.y()), (2, 6));
+        let pos: Position = "10 10".parse().unwrap();
+        assert_eq!(pos.yx(), GAME_BOARD_SIZE * GAME_BOARD_SIZE - 1);
+
+        assert!("".parse::<Position>().is_err());
+        assert!("1".parse::<Position>().is_err());
+        assert!("1 1 1".parse::<Position>().is_err());
+        assert!("0 1".parse::<Position>().is_err());
+        assert!("1 11".parse::<Position>().is_err());
+        assert!("-1 1".parse::<Position>().is_err());
+        assert!("a b".parse::<Position>().is_err());
+    }
+
+    #[test]
+    fn test_Position_parse_list() {
+        let positions = Position::parse_list("1 1 10 10\r").unwrap();
+        assert_eq!(
+            positions.iter().map(|pos| pos.yx()).collect::<Vec<_>>(),
+            vec![0, GAME_BOARD_SIZE * GAME_BOARD_SIZE - 1]
+        );
+        assert!(Position::parse_list("").unwrap().is_empty());
+        assert!(Position::parse_list("1 1 10").is_err());
+        assert!(Position::parse_list("1 1 10 11").is_err());
+    }
+
+    #[test]
+    fn test_Position_neighbours() {
+        let neighbours = |pos: &str| {
+            let mut neighbours: Vec<String> = pos
+                .parse::<Position>()
+                .unwrap()
+                .neighbours()
+                .iter()
+                .map(Position::to_string)
+                .collect();
+            neighbours.sort();
+            neighbours
+        };
+        assert_eq!(neighbours("1 1"), vec!["1 2", "2 1", "2 2"]);
+        assert_eq!(
+            neighbours("10 5"),
+            vec!["10 4", "10 6", "9 4", "9 5", "9 6"]
+        );
+        assert_eq!(neighbours("5 5").len(), 8);
+    }
+
+    #[test]
+    fn test_Position_transform() {
+        let position: Position = "2 4".parse().unwrap();
+        let transformed = |symmetry| position.transform(symmetry).to_string();
+        assert_eq!(transformed(Symmetry::Identity), "2 4");
+        assert_eq!(transformed(Symmetry::Rotate90), "7 2");
+        assert_eq!(transformed(Symmetry::Rotate180), "9 7");
+        assert_eq!(transformed(Symmetry::Rotate270), "4 9");
+        assert_eq!(transformed(Symmetry::ReflectHorizontally), "9 4");
+        assert_eq!(transformed(Symmetry::ReflectVertically), "2 7");
+        assert_eq!(transformed(Symmetry::ReflectDiagonally), "4 2");
+        assert_eq!(transformed(Symmetry::ReflectAntiDiagonally), "7 9");
+        assert_eq!(
+            position.rotate_90().rotate_90(),
+            position.transform(Symmetry::Rotate180)
+        );
+        assert_eq!(
+            position.reflect_horizontally().reflect_vertically(),
+            position.rotate_180()
+        );
+
+        for &symmetry in &Symmetry::ALL {
+            // Every symmetry is a permutation of the board cells, which is undone by its inverse
+            let mut images: Vec<u8> = Position::top_left()
+                .iter()
+                .map(|position| position.transform(symmetry).yx())
+                .collect();
+            for position in Position::top_left().iter() {
+                assert_eq!(
+                    position.transform(symmetry).transform(symmetry.inverse()),
+                    position
+                );
+            }
+            images.sort();
+            images.dedup();
+            assert_eq!(images.len(), 100);
+            // Distances between the cells are preserved
+            let (a, b) = (position, position.get_right().unwrap());
+            let (a, b) = (a.transform(symmetry), b.transform(symmetry));
+            let distance = (i16::from(a.x()) - i16::from(b.x())).abs()
+                + (i16::from(a.y()) - i16::from(b.y())).abs();
+            assert_eq!(distance, 1);
+        }
+    }
+
+    #[test]
+    fn test_Position_from_xy() {
+        assert_eq!(Position::from_xy(0, 0), Some(Position::top_left()));
+        assert_eq!(Position::from_xy(9, 9), Some(Position::bottom_right()));
+        assert_eq!(Position::from_xy(2, 4), "3 5".parse().ok());
+        assert_eq!(Position::from_xy(10, 0), None);
+        assert_eq!(Position::from_xy(0, 10), None);
+    }
 }
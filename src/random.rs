@@ -0,0 +1,73 @@
+/// A small xorshift64* pseudo-random number generator, which is good enough to place ships and
+/// pick shots, and produces the same sequence for the same seed.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // Mix the seed (splitmix64), so that close seeds produce unrelated sequences and the
+        // state is never zero.
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+        Self {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    /// Seeds the generator from the current time.
+    pub fn from_time() -> Self {
+        Self::new(Self::seed_from_time())
+    }
+
+    /// A seed taken from the current time, for the callers which need to report it.
+    pub fn seed_from_time() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs() ^ (u64::from(duration.subsec_nanos()) << 32))
+            .unwrap_or(0)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in the `0..n` range.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_Random_is_reproducible() {
+        let numbers = |seed| {
+            let mut random = Random::new(seed);
+            (0..5).map(|_| random.next_u64()).collect::<Vec<u64>>()
+        };
+        assert_eq!(numbers(42), numbers(42));
+        assert_ne!(numbers(42), numbers(43));
+        assert_ne!(numbers(0), vec![0; 5]);
+    }
+
+    #[test]
+    fn test_Random_below() {
+        let mut random = Random::new(1);
+        let mut seen = [false; 10];
+        for _ in 0..1000 {
+            seen[random.below(10)] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
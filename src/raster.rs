@@ -0,0 +1,542 @@
+/// A paletted image: every pixel is an index into a palette that is only given when the image is
+/// encoded, so the same drawing can be saved into PNG or GIF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    width: u16,
+    height: u16,
+    pixels: Vec<u8>,
+}
+
+impl Raster {
+    pub fn new(width: u16, height: u16, color: u8) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; usize::from(width) * usize::from(height)],
+        }
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, x: u16, y: u16) -> u8 {
+        self.pixels[usize::from(y) * usize::from(self.width) + usize::from(x)]
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: u8) {
+        if x >= 0 && y >= 0 && x < i32::from(self.width) && y < i32::from(self.height) {
+            self.pixels[y as usize * usize::from(self.width) + x as usize] = color;
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: u8) {
+        for y in y..y + height {
+            for x in x..x + width {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Fills the pixels whose centers are within the `radius` from the `center`.
+    pub fn fill_circle(&mut self, center: (f32, f32), radius: f32, color: u8) {
+        let (cx, cy) = center;
+        for y in (cy - radius).floor() as i32..=(cy + radius).ceil() as i32 {
+            for x in (cx - radius).floor() as i32..=(cx + radius).ceil() as i32 {
+                let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Draws a line with round caps: fills the pixels whose centers are within the half of the
+    /// `width` from the segment.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: u8) {
+        let ((x0, y0), (x1, y1)) = (from, to);
+        let half_width = width / 2.0;
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length_squared = dx * dx + dy * dy;
+        let min_x = x0.min(x1) - half_width;
+        let max_x = x0.max(x1) + half_width;
+        let min_y = y0.min(y1) - half_width;
+        let max_y = y0.max(y1) + half_width;
+        for y in min_y.floor() as i32..=max_y.ceil() as i32 {
+            for x in min_x.floor() as i32..=max_x.ceil() as i32 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                // The closest point of the segment is at `t` of its length
+                let mut t = 0.0;
+                if length_squared > 0.0 {
+                    t = ((px - x0) * dx + (py - y0) * dy) / length_squared;
+                    t = t.max(0.0);
+                    t = t.min(1.0);
+                }
+                let (ex, ey) = (px - (x0 + t * dx), py - (y0 + t * dy));
+                if ex * ex + ey * ey <= half_width * half_width {
+                    self.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Width of the `text` written with the built-in font enlarged `scale` times.
+    pub fn text_width(text: &str, scale: i32) -> i32 {
+        let length = text.chars().count() as i32;
+        if length == 0 {
+            0
+        } else {
+            (length * 4 - 1) * scale
+        }
+    }
+
+    /// Writes the `text` with the built-in 3x5 pixels font (letters are written in upper case)
+    /// enlarged `scale` times and centered at the `center`.
+    pub fn text(&mut self, center: (i32, i32), text: &str, scale: i32, color: u8) {
+        let mut x = center.0 - Self::text_width(text, scale) / 2;
+        let y = center.1 - 5 * scale / 2;
+        for c in text.chars() {
+            for (row, bits) in font_glyph(c).iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        self.fill_rect(
+                            x + column * scale,
+                            y + row as i32 * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+            x += 4 * scale;
+        }
+    }
+
+    /// Encodes the image into PNG with the `palette` colors.
+    pub fn to_png(&self, palette: &[[u8; 3]]) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&u32::from(self.width).to_be_bytes());
+        header.extend_from_slice(&u32::from(self.height).to_be_bytes());
+        // 8 bits per pixel, indexed colors, default compression, filtering and no interlace
+        header.extend_from_slice(&[8, 3, 0, 0, 0]);
+        write_png_chunk(&mut png, b"IHDR", &header);
+
+        let palette: Vec<u8> = palette
+            .iter()
+            .flat_map(|color| color.iter().cloned())
+            .collect();
+        write_png_chunk(&mut png, b"PLTE", &palette);
+
+        // Every row starts with the filter type, which is 0 (none)
+        let mut scanlines = Vec::with_capacity(self.pixels.len() + usize::from(self.height));
+        for row in self.pixels.chunks(usize::from(self.width)) {
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+        write_png_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+
+        write_png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+/// A frame of an animation shown for `delay` hundredths of a second.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub raster: Raster,
+    pub delay: u16,
+}
+
+/// Encodes the frames (all of the same size) into a looped GIF animation with the `palette`
+/// colors (up to 256 of them).
+pub fn encode_gif(frames: &[Frame], palette: &[[u8; 3]]) -> Vec<u8> {
+    let (width, height) = frames
+        .first()
+        .map_or((0, 0), |frame| (frame.raster.width, frame.raster.height));
+    // The palette size is a power of two, and LZW needs at least 2 bits per pixel
+    let mut color_bits = 2;
+    while 1 << color_bits < palette.len() {
+        color_bits += 1;
+    }
+
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&width.to_le_bytes());
+    gif.extend_from_slice(&height.to_le_bytes());
+    // The global color table with 8 bits per primary color, no background color and aspect ratio
+    gif.extend_from_slice(&[0xF0 | (color_bits - 1), 0, 0]);
+    for index in 0..1 << color_bits {
+        gif.extend_from_slice(palette.get(index).unwrap_or(&[0, 0, 0]));
+    }
+    // Loop the animation forever
+    gif.extend_from_slice(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for frame in frames {
+        gif.extend_from_slice(&[0x21, 0xF9, 4, 0]);
+        gif.extend_from_slice(&frame.delay.to_le_bytes());
+        gif.extend_from_slice(&[0, 0]);
+
+        gif.push(0x2C);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&width.to_le_bytes());
+        gif.extend_from_slice(&height.to_le_bytes());
+        gif.push(0);
+        gif.push(color_bits);
+        for block in lzw_encode(&frame.raster.pixels, color_bits).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0);
+    }
+    gif.push(0x3B);
+    gif
+}
+
+/// Compresses the pixels with the variable-length-code LZW as GIF requires: the codes start
+/// with `min_code_size + 1` bits and grow up to 12 bits, and the table is cleared once it is
+/// full.
+fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear_code = 1u16 << min_code_size;
+    let end_code = clear_code + 1;
+    let mut writer = BitWriter::default();
+    let mut code_size = min_code_size + 1;
+    let mut next_code = end_code + 1;
+    let mut table = std::collections::HashMap::new();
+
+    writer.write(clear_code, code_size);
+    let mut pixels = pixels.iter();
+    let mut prefix = match pixels.next() {
+        Some(&pixel) => u16::from(pixel),
+        None => {
+            writer.write(end_code, code_size);
+            return writer.finish();
+        }
+    };
+    for &pixel in pixels {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+        writer.write(prefix, code_size);
+        table.insert((prefix, pixel), next_code);
+        if next_code >= 1 << code_size {
+            code_size += 1;
+        }
+        if next_code == 4095 {
+            writer.write(clear_code, code_size);
+            table.clear();
+            code_size = min_code_size + 1;
+            next_code = end_code + 1;
+        } else {
+            next_code += 1;
+        }
+        prefix = u16::from(pixel);
+    }
+    writer.write(prefix, code_size);
+    writer.write(end_code, code_size);
+    writer.finish()
+}
+
+/// Packs codes into bytes starting from the least significant bits.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits_count: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, code_size: u8) {
+        self.buffer |= u32::from(code) << self.bits_count;
+        self.bits_count += code_size;
+        while self.bits_count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits_count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits_count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+fn write_png_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Wraps the data into a zlib stream of uncompressed ("stored") deflate blocks, which every
+/// decoder understands, and the images are small anyway.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_SIZE: usize = 0xFFFF;
+    let mut zlib = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK_SIZE * 5 + 11);
+    zlib.extend_from_slice(&[0x78, 0x01]);
+    let blocks: Vec<&[u8]> = if data.is_empty() {
+        vec![data]
+    } else {
+        data.chunks(MAX_BLOCK_SIZE).collect()
+    };
+    for (block_index, block) in blocks.iter().enumerate() {
+        // The first bit marks the final block
+        zlib.push(u8::from(block_index + 1 == blocks.len()));
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    zlib
+}
+
+/// Rows of the 3x5 pixels glyph of the character, the highest of the 3 bits is the left pixel.
+fn font_glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 3, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 2, 2, 2],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        'A' => [2, 5, 7, 5, 5],
+        'B' => [6, 5, 6, 5, 6],
+        'C' => [3, 4, 4, 4, 3],
+        'D' => [6, 5, 5, 5, 6],
+        'E' => [7, 4, 6, 4, 7],
+        'F' => [7, 4, 6, 4, 4],
+        'G' => [3, 4, 5, 5, 3],
+        'H' => [5, 5, 7, 5, 5],
+        'I' => [7, 2, 2, 2, 7],
+        'J' => [1, 1, 1, 5, 2],
+        'K' => [5, 5, 6, 5, 5],
+        'L' => [4, 4, 4, 4, 7],
+        'M' => [5, 7, 7, 5, 5],
+        'N' => [6, 5, 5, 5, 5],
+        'O' => [2, 5, 5, 5, 2],
+        'P' => [6, 5, 6, 4, 4],
+        'Q' => [2, 5, 5, 6, 3],
+        'R' => [6, 5, 6, 5, 5],
+        'S' => [3, 4, 2, 1, 6],
+        'T' => [7, 2, 2, 2, 2],
+        'U' => [5, 5, 5, 5, 7],
+        'V' => [5, 5, 5, 5, 2],
+        'W' => [5, 5, 7, 7, 5],
+        'X' => [5, 5, 2, 5, 5],
+        'Y' => [5, 5, 2, 2, 2],
+        'Z' => [7, 1, 2, 4, 7],
+        ' ' => [0, 0, 0, 0, 0],
+        '!' => [2, 2, 2, 0, 2],
+        '-' => [0, 0, 7, 0, 0],
+        ':' => [0, 2, 0, 2, 0],
+        '.' => [0, 0, 0, 0, 2],
+        ',' => [0, 0, 0, 2, 4],
+        '/' => [1, 1, 2, 4, 4],
+        '(' => [1, 2, 2, 2, 1],
+        ')' => [4, 2, 2, 2, 4],
+        _ => [7, 1, 2, 0, 2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_Raster_draw() {
+        let mut raster = Raster::new(8, 8, 0);
+        raster.fill_rect(-1, -1, 3, 3, 1);
+        assert_eq!(
+            (raster.get(0, 0), raster.get(1, 1), raster.get(2, 2)),
+            (1, 1, 0)
+        );
+        raster.line((2.5, 6.5), (8.0, 6.5), 1.0, 2);
+        assert_eq!(
+            (raster.get(1, 6), raster.get(2, 6), raster.get(7, 6)),
+            (0, 2, 2)
+        );
+        assert_eq!(raster.get(4, 5), 0);
+        raster.fill_circle((4.0, 3.0), 1.0, 3);
+        assert_eq!(
+            (raster.get(3, 2), raster.get(4, 3), raster.get(5, 3)),
+            (3, 3, 0)
+        );
+
+        let mut raster = Raster::new(9, 7, 0);
+        raster.text((4, 3), "10", 1, 1);
+        assert_eq!(Raster::text_width("10", 1), 7);
+        let rows: Vec<String> = raster
+            .pixels
+            .chunks(9)
+            .map(|row| row.iter().map(|&pixel| char::from(b'0' + pixel)).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "000000000",
+                "001001110",
+                "011001010",
+                "001001010",
+                "001001010",
+                "011101110",
+                "000000000",
+            ]
+        );
+    }
+
+    /// Decodes the GIF LZW stream the way the GIF specification describes it.
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear_code = 1usize << min_code_size;
+        let end_code = clear_code + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut code_size = min_code_size + 1;
+        let mut previous: Option<usize> = None;
+        let mut pixels = Vec::new();
+        let (mut buffer, mut bits_count, mut bytes) = (0u32, 0u8, data.iter());
+        loop {
+            while bits_count < code_size {
+                buffer |= u32::from(*bytes.next().unwrap()) << bits_count;
+                bits_count += 8;
+            }
+            let code = (buffer & ((1 << code_size) - 1)) as usize;
+            buffer >>= code_size;
+            bits_count -= code_size;
+
+            if code == clear_code {
+                table = (0..=end_code).map(|code| vec![code as u8]).collect();
+                code_size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == end_code {
+                return pixels;
+            }
+            let entry = match previous {
+                None => table[code].clone(),
+                Some(previous) => {
+                    let mut entry = table[previous].clone();
+                    let first = if code < table.len() {
+                        table[code][0]
+                    } else {
+                        entry[0]
+                    };
+                    entry.push(first);
+                    if table.len() < 4096 {
+                        table.push(entry);
+                    }
+                    if table.len() == 1 << code_size && code_size < 12 {
+                        code_size += 1;
+                    }
+                    table[code].clone()
+                }
+            };
+            pixels.extend_from_slice(&entry);
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn test_lzw_encode() {
+        let mut random = crate::random::Random::new(1);
+        let noise: Vec<u8> = (0..20000).map(|_| random.below(16) as u8).collect();
+        let stripes: Vec<u8> = (0..100_000).map(|index| (index / 37 % 3) as u8).collect();
+        for pixels in &[vec![], vec![3], vec![0; 5000], noise, stripes] {
+            assert_eq!(&lzw_decode(&lzw_encode(pixels, 4), 4), pixels);
+            assert_eq!(&lzw_decode(&lzw_encode(pixels, 8), 8), pixels);
+        }
+        assert!(lzw_encode(&[0; 5000], 4).len() < 200);
+
+        // The image data of the 10x10 sample image from "What's in a GIF" by Matthew Flickinger,
+        // encoded by an independent encoder
+        let pixels: Vec<u8> = [
+            "1111122222",
+            "1111122222",
+            "1111122222",
+            "1110000222",
+            "1110000222",
+            "2220000111",
+            "2220000111",
+            "2222211111",
+            "2222211111",
+            "2222211111",
+        ]
+        .concat()
+        .bytes()
+        .map(|digit| digit - b'0')
+        .collect();
+        assert_eq!(
+            lzw_encode(&pixels, 2),
+            [
+                0x8C, 0x2D, 0x99, 0x87, 0x2A, 0x1C, 0xDC, 0x33, 0xA0, 0x02, 0x75, 0xEC, 0x95, 0xFA,
+                0xA8, 0xDE, 0x60, 0x8C, 0x04, 0x91, 0x4C, 0x01,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_gif() {
+        let frame = |color| Frame {
+            raster: Raster::new(3, 2, color),
+            delay: 50,
+        };
+        let gif = encode_gif(
+            &[frame(0), frame(1)],
+            &[[0, 0, 0], [255, 255, 255], [9, 9, 9]],
+        );
+        assert_eq!(&gif[..13], b"GIF89a\x03\x00\x02\x00\xF1\x00\x00");
+        assert_eq!(&gif[13..25], &[0, 0, 0, 255, 255, 255, 9, 9, 9, 0, 0, 0]);
+        assert_eq!(gif.last(), Some(&0x3B));
+        assert_eq!(
+            gif.windows(4)
+                .filter(|bytes| bytes == b"\x21\xF9\x04\x00")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_Raster_to_png() {
+        let mut raster = Raster::new(3, 2, 0);
+        raster.fill_rect(1, 0, 1, 2, 1);
+        let png = raster.to_png(&[[255, 255, 255], [0, 0, 0]]);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(
+            zlib_stored(&[0, 0, 1, 0, 0, 0, 1, 0]),
+            [0x78, 0x01, 1, 8, 0, 0xF7, 0xFF, 0, 0, 1, 0, 0, 0, 1, 0, 0x00, 0x10, 0x00, 0x03]
+        );
+    }
+}
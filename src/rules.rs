@@ -0,0 +1,434 @@
+use structopt::StructOpt;
+
+use crate::board::{Board, GameBoardShotResult, ShotReport};
+use crate::fleet::Fleet;
+use crate::position::Position;
+use crate::random::Random;
+use crate::GameResult;
+
+/// What happens when a player shoots at a cell that has already been shot at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RepeatShotPolicy {
+    /// A repeated shot is reported as a miss.
+    Miss,
+    /// The player who repeats a shot loses the game.
+    Forfeit,
+    /// Repeated shots are reported as misses, but a player who makes more than the given number
+    /// of shots in total loses the game.
+    Limit(u16),
+}
+
+impl std::str::FromStr for RepeatShotPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const LIMIT_PREFIX: &str = "limit:";
+        match s {
+            "miss" => Ok(Self::Miss),
+            "forfeit" => Ok(Self::Forfeit),
+            _ if s.starts_with(LIMIT_PREFIX) => s[LIMIT_PREFIX.len()..]
+                .parse()
+                .map(Self::Limit)
+                .map_err(|err| format!("invalid shots limit: {}", err)),
+            _ => Err("expected `miss`, `forfeit` or `limit:<shots>`".to_owned()),
+        }
+    }
+}
+
+/// How many shots a player makes during their turn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TurnPolicy {
+    /// A player shoots until they miss.
+    Classic,
+    /// Players make a single shot each in turn regardless of its result.
+    Alternate,
+    /// A player fires a volley of as many shots as many ships they have afloat.
+    Salvo,
+}
+
+impl std::str::FromStr for TurnPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Self::Classic),
+            "alternate" => Ok(Self::Alternate),
+            "salvo" => Ok(Self::Salvo),
+            _ => Err("expected `classic`, `alternate` or `salvo`".to_owned()),
+        }
+    }
+}
+
+/// How ships are allowed to touch each other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlacementRule {
+    /// Ships cannot touch each other, not even by corners.
+    NoContact,
+    /// Ships can touch each other by corners only.
+    Corners,
+    /// Ships can touch each other in any way.
+    Any,
+}
+
+impl std::str::FromStr for PlacementRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no-contact" => Ok(Self::NoContact),
+            "corners" => Ok(Self::Corners),
+            "any" => Ok(Self::Any),
+            _ => Err("expected `no-contact`, `corners` or `any`".to_owned()),
+        }
+    }
+}
+
+/// How the game is decided when both players have made the maximum number of shots.
+#[derive(Debug, Copy, Clone)]
+pub enum Adjudication {
+    /// The game is a draw.
+    Draw,
+    /// The player who has fewer hits left to sink the opponent's fleet wins.
+    HitsLeft,
+}
+
+impl std::str::FromStr for Adjudication {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draw" => Ok(Self::Draw),
+            "hits-left" => Ok(Self::HitsLeft),
+            _ => Err("expected `draw` or `hits-left`".to_owned()),
+        }
+    }
+}
+
+/// What happens to a player who hits a mine of the opponent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MinePenalty {
+    /// The player skips their next turn.
+    SkipTurn,
+    /// One of the player's ship cells is revealed to the opponent.
+    RevealShip,
+}
+
+impl std::str::FromStr for MinePenalty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip-turn" => Ok(Self::SkipTurn),
+            "reveal" => Ok(Self::RevealShip),
+            _ => Err("expected `skip-turn` or `reveal`".to_owned()),
+        }
+    }
+}
+
+/// A violation of the game rules by a shot that is otherwise well-formed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RuleViolation {
+    RepeatedShot,
+    ShotsLimitExceeded,
+}
+
+impl RuleViolation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RepeatedShot => "repeat",
+            Self::ShotsLimitExceeded => "limit",
+        }
+    }
+}
+
+impl std::str::FromStr for RuleViolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "repeat" => Ok(Self::RepeatedShot),
+            "limit" => Ok(Self::ShotsLimitExceeded),
+            _ => Err("expected `repeat` or `limit`".to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, structopt::StructOpt)]
+pub struct GameRules {
+    /// Ships to place as a comma-separated list of `<count>x<shape>`, where the shape is drawn
+    /// with `#` and `_` row by row with rows separated by `/` (e.g. `1x#_/#_/##` is a single
+    /// L-shaped ship); ships can be rotated and reflected
+    #[structopt(long = "fleet", default_value = "1x####,2x###,3x##,4x#")]
+    pub fleet: Fleet,
+    /// How ships are allowed to touch each other: `no-contact`, `corners` or `any` (mark each
+    /// ship with its own letter to place ships side by side)
+    #[structopt(long = "placement", default_value = "no-contact")]
+    pub placement: PlacementRule,
+    /// Number of mines (`*` cells) each player places on their board
+    #[structopt(long = "mines", default_value = "0")]
+    pub mines: u8,
+    /// What happens to a player who hits a mine: `skip-turn` (the player skips their next turn)
+    /// or `reveal` (one of the player's ship cells is revealed to the opponent)
+    #[structopt(long = "mine-penalty", default_value = "skip-turn")]
+    pub mine_penalty: MinePenalty,
+    /// How many shots a player makes during their turn: `classic` (shoot until miss),
+    /// `alternate` (a single shot regardless of its result) or `salvo` (a volley of as many shots
+    /// as many ships the player has afloat)
+    #[structopt(long = "turns", default_value = "classic")]
+    pub turns: TurnPolicy,
+    /// Reveal the cells around a sunk ship as shot and report the cells of the sunk ship to the
+    /// shooter (e.g. `sunk 1 1 1 2`); requires the `no-contact` placement and no mines
+    #[structopt(long = "reveal-sunk")]
+    pub reveal_sunk: bool,
+    /// What happens when a player shoots at the same cell again: `miss`, `forfeit`, or
+    /// `limit:<shots>` to count repeated shots as misses but limit the total number of shots
+    #[structopt(long = "repeat-shots", default_value = "miss")]
+    pub repeat_shots: RepeatShotPolicy,
+    /// Maximum number of shots per player, after which the game is adjudicated
+    #[structopt(long = "max-shots", default_value = "200")]
+    pub max_shots: u16,
+    /// How the game is decided once both players made the maximum number of shots: `draw` or
+    /// `hits-left` (the player who has fewer hits left to sink the opponent's fleet wins)
+    #[structopt(long = "adjudication", default_value = "hits-left")]
+    pub adjudication: Adjudication,
+}
+
+impl Default for GameRules {
+    /// The rules the judge plays by when no options are given.
+    fn default() -> Self {
+        Self::from_iter(&["judge"])
+    }
+}
+
+impl GameRules {
+    /// Checks that the options can be played together: the cells around a sunk ship can only be
+    /// revealed as empty if neither ships nor mines are allowed there.
+    pub fn check(&self) -> Result<(), String> {
+        if self.reveal_sunk && (self.placement != PlacementRule::NoContact || self.mines != 0) {
+            return Err(
+                "`--reveal-sunk` requires `--placement no-contact` and no mines".to_owned(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks whether the player is allowed to make their `shots_count`-th shot at the
+    /// `position` of the opponent's `board`.
+    pub fn check_shot<B: Board>(
+        &self,
+        board: &B,
+        position: Position,
+        shots_count: u16,
+    ) -> Result<(), RuleViolation> {
+        match self.repeat_shots {
+            RepeatShotPolicy::Miss => Ok(()),
+            RepeatShotPolicy::Forfeit => {
+                if board.get(position).is_shot() {
+                    Err(RuleViolation::RepeatedShot)
+                } else {
+                    Ok(())
+                }
+            }
+            RepeatShotPolicy::Limit(max_shots) => {
+                if shots_count > max_shots {
+                    Err(RuleViolation::ShotsLimitExceeded)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Makes the `shots_count`-th shot of a player at the `position` of the opponent's board if
+    /// the rules allow that. If the shot hits a mine and the rules penalize that by revealing one
+    /// of the player's own ship cells, the cell is picked at random on the player's `own_board`.
+    pub fn apply_shot<B: Board>(
+        &self,
+        shots_count: u16,
+        own_board: &mut B,
+        opponent_board: &mut B,
+        position: Position,
+        random: &mut Random,
+    ) -> Result<ShotReport, RuleViolation> {
+        self.check_shot(opponent_board, position, shots_count)?;
+        let mut shot_report = ShotReport::from(opponent_board.shoot(position));
+        match shot_report.result {
+            GameBoardShotResult::Sunk if self.reveal_sunk => {
+                shot_report.sunk_ship = opponent_board.reveal_around_ship(position);
+            }
+            GameBoardShotResult::Mine if self.mine_penalty == MinePenalty::RevealShip => {
+                shot_report.revealed_cell = own_board.reveal_ship_cell(random);
+            }
+            _ => (),
+        }
+        Ok(shot_report)
+    }
+
+    /// Checks whether the report of a shot with the given result carries cells: the cells of the
+    /// sunk ship or the player's own ship cell revealed as a penalty for hitting a mine.
+    pub fn reveals_cells(&self, shot_result: GameBoardShotResult) -> bool {
+        match shot_result {
+            GameBoardShotResult::Sunk => self.reveal_sunk,
+            GameBoardShotResult::Mine => self.mine_penalty == MinePenalty::RevealShip,
+            GameBoardShotResult::Miss | GameBoardShotResult::Hit => false,
+        }
+    }
+
+    /// Checks whether the player who made a shot with the given result skips their next turn.
+    pub fn skips_next_turn(&self, shot_result: GameBoardShotResult) -> bool {
+        shot_result == GameBoardShotResult::Mine && self.mine_penalty == MinePenalty::SkipTurn
+    }
+
+    /// Decides the game that has reached the maximum number of shots given the number of hits
+    /// each player still needs to sink the opponent's fleet.
+    pub fn adjudicate(&self, player1_hits_left: u8, player2_hits_left: u8) -> GameResult {
+        match self.adjudication {
+            Adjudication::Draw => GameResult::Draw,
+            Adjudication::HitsLeft => match player1_hits_left.cmp(&player2_hits_left) {
+                std::cmp::Ordering::Less => GameResult::Player1Win,
+                std::cmp::Ordering::Equal => GameResult::Draw,
+                std::cmp::Ordering::Greater => GameResult::Player2Win,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures::{board, rules};
+
+    #[test]
+    fn test_RepeatShotPolicy_from_str() {
+        assert!(match "miss".parse() {
+            Ok(RepeatShotPolicy::Miss) => true,
+            _ => false,
+        });
+        assert!(match "forfeit".parse() {
+            Ok(RepeatShotPolicy::Forfeit) => true,
+            _ => false,
+        });
+        assert!(match "limit:150".parse() {
+            Ok(RepeatShotPolicy::Limit(150)) => true,
+            _ => false,
+        });
+        assert!("limit:".parse::<RepeatShotPolicy>().is_err());
+        assert!("limit".parse::<RepeatShotPolicy>().is_err());
+        assert!("".parse::<RepeatShotPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_TurnPolicy_from_str() {
+        assert_eq!("classic".parse(), Ok(TurnPolicy::Classic));
+        assert_eq!("alternate".parse(), Ok(TurnPolicy::Alternate));
+        assert_eq!("salvo".parse(), Ok(TurnPolicy::Salvo));
+        assert!("Salvo".parse::<TurnPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_PlacementRule_from_str() {
+        assert_eq!("no-contact".parse(), Ok(PlacementRule::NoContact));
+        assert_eq!("corners".parse(), Ok(PlacementRule::Corners));
+        assert_eq!("any".parse(), Ok(PlacementRule::Any));
+        assert!("none".parse::<PlacementRule>().is_err());
+    }
+
+    #[test]
+    fn test_MinePenalty_from_str() {
+        assert_eq!("skip-turn".parse(), Ok(MinePenalty::SkipTurn));
+        assert_eq!("reveal".parse(), Ok(MinePenalty::RevealShip));
+        assert!("explode".parse::<MinePenalty>().is_err());
+    }
+
+    #[test]
+    fn test_GameRules_check() {
+        for &args in &[
+            &[][..],
+            &["--reveal-sunk"],
+            &["--placement", "any", "--mines", "3"],
+        ] {
+            assert!(rules(args).check().is_ok(), "{:?}", args);
+        }
+        for &args in &[
+            &["--reveal-sunk", "--placement", "corners"][..],
+            &["--reveal-sunk", "--placement", "any"],
+            &["--reveal-sunk", "--mines", "3"],
+        ] {
+            assert!(rules(args).check().is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_GameRules_check_shot() {
+        let mut board = board();
+        let position = Position::top_left();
+        board.shoot(position);
+
+        let rules = GameRules {
+            repeat_shots: RepeatShotPolicy::Miss,
+            ..GameRules::default()
+        };
+        assert!(rules.check_shot(&board, position, 1000).is_ok());
+
+        let rules = GameRules {
+            repeat_shots: RepeatShotPolicy::Forfeit,
+            ..GameRules::default()
+        };
+        assert!(rules
+            .check_shot(&board, position.get_right().unwrap(), 2)
+            .is_ok());
+        assert!(rules.check_shot(&board, position, 2).is_err());
+
+        let rules = GameRules {
+            repeat_shots: RepeatShotPolicy::Limit(2),
+            ..GameRules::default()
+        };
+        assert!(rules.check_shot(&board, position, 2).is_ok());
+        assert!(rules.check_shot(&board, position, 3).is_err());
+    }
+
+    #[test]
+    fn test_GameRules_skips_next_turn() {
+        let rules = GameRules::default();
+        assert!(rules.skips_next_turn(GameBoardShotResult::Mine));
+        assert!(!rules.skips_next_turn(GameBoardShotResult::Miss));
+
+        let rules = GameRules {
+            mine_penalty: MinePenalty::RevealShip,
+            ..rules
+        };
+        assert!(!rules.skips_next_turn(GameBoardShotResult::Mine));
+    }
+
+    #[test]
+    fn test_GameRules_reveals_cells() {
+        let rules = GameRules::default();
+        assert!(!rules.reveals_cells(GameBoardShotResult::Sunk));
+        assert!(!rules.reveals_cells(GameBoardShotResult::Mine));
+
+        let rules = GameRules {
+            reveal_sunk: true,
+            mine_penalty: MinePenalty::RevealShip,
+            ..rules
+        };
+        assert!(rules.reveals_cells(GameBoardShotResult::Sunk));
+        assert!(rules.reveals_cells(GameBoardShotResult::Mine));
+        assert!(!rules.reveals_cells(GameBoardShotResult::Hit));
+    }
+
+    #[test]
+    fn test_GameRules_adjudicate() {
+        let rules = GameRules::default();
+        assert_eq!(rules.adjudicate(3, 5), GameResult::Player1Win);
+        assert_eq!(rules.adjudicate(5, 3), GameResult::Player2Win);
+        assert_eq!(rules.adjudicate(3, 3), GameResult::Draw);
+
+        let rules = GameRules {
+            adjudication: Adjudication::Draw,
+            ..rules
+        };
+        assert_eq!(rules.adjudicate(3, 5), GameResult::Draw);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::position::{Position, Symmetry};
+use crate::InvalidInputError;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShipOrientation {
+    /// Single-cell ships have no orientation.
+    Point,
+    Horizontal,
+    Vertical,
+    /// Ships that are not straight lines, e.g. L-shaped ones.
+    Irregular,
+}
+
+impl ShipOrientation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Point => "point",
+            Self::Horizontal => "horizontal",
+            Self::Vertical => "vertical",
+            Self::Irregular => "irregular",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ship {
+    /// Character the ship was marked with on the original map (`#` or a letter).
+    label: char,
+    /// Cells of the ship ordered from the top-left one.
+    cells: Vec<Position>,
+    size: u8,
+    orientation: ShipOrientation,
+    hits: u8,
+}
+
+impl Ship {
+    /// Creates a ship from a connected set of cells.
+    pub fn new(label: char, mut cells: Vec<Position>) -> Result<Self, InvalidInputError> {
+        cells.sort_by_key(|cell| cell.yx());
+        if cells.is_empty() {
+            return Err(InvalidInputError {});
+        }
+        Ok(Self {
+            label,
+            size: cells.len() as u8,
+            orientation: Self::orientation_of(&cells),
+            cells,
+            hits: 0,
+        })
+    }
+
+    /// The orientation of a ship with the (non-empty) cells.
+    fn orientation_of(cells: &[Position]) -> ShipOrientation {
+        let first_cell = cells[0];
+        if cells.len() == 1 {
+            ShipOrientation::Point
+        } else if cells.iter().all(|cell| cell.y() == first_cell.y()) {
+            ShipOrientation::Horizontal
+        } else if cells.iter().all(|cell| cell.x() == first_cell.x()) {
+            ShipOrientation::Vertical
+        } else {
+            ShipOrientation::Irregular
+        }
+    }
+
+    pub fn label(&self) -> char {
+        self.label
+    }
+
+    pub fn cells(&self) -> &[Position] {
+        &self.cells
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn orientation(&self) -> ShipOrientation {
+        self.orientation
+    }
+
+    pub fn hits(&self) -> u8 {
+        self.hits
+    }
+
+    pub fn is_sunk(&self) -> bool {
+        self.hits == self.size
+    }
+
+    /// Registers a hit into a not yet hit cell of the ship.
+    pub fn hit(&mut self) {
+        self.hits += 1;
+    }
+
+    /// The same ship with the same hits on the board rotated or mirrored by the `symmetry`.
+    pub fn transform(&self, symmetry: Symmetry) -> Self {
+        let mut cells: Vec<Position> = self
+            .cells
+            .iter()
+            .map(|cell| cell.transform(symmetry))
+            .collect();
+        cells.sort_by_key(|cell| cell.yx());
+        Self {
+            orientation: Self::orientation_of(&cells),
+            cells,
+            ..self.clone()
+        }
+    }
+}
+
+/// Names the ship by its size, orientation and the top-left cell, e.g.
+/// `3-cell horizontal ship at 1 3`, adding the label if the ship was marked with a letter.
+impl std::fmt::Display for Ship {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-cell {} ship at {}",
+            self.size,
+            self.orientation().as_str(),
+            self.cells[0]
+        )?;
+        if self.label != '#' {
+            write!(f, " ({})", self.label)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    fn cells(s: &str) -> Vec<Position> {
+        Position::parse_list(s).unwrap()
+    }
+
+    #[test]
+    fn test_Ship_new() {
+        let ship = Ship::new('#', cells("3 2 1 2 2 2")).unwrap();
+        assert_eq!(ship.cells(), &cells("1 2 2 2 3 2")[..]);
+        assert_eq!(ship.size(), 3);
+        assert_eq!(ship.orientation(), ShipOrientation::Horizontal);
+        assert_eq!(ship.to_string(), "3-cell horizontal ship at 1 2");
+
+        let ship = Ship::new('A', cells("4 4 4 5")).unwrap();
+        assert_eq!(ship.orientation(), ShipOrientation::Vertical);
+        assert_eq!(ship.to_string(), "2-cell vertical ship at 4 4 (A)");
+
+        let ship = Ship::new('#', cells("7 7")).unwrap();
+        assert_eq!(ship.orientation(), ShipOrientation::Point);
+
+        let ship = Ship::new('#', cells("1 1 2 1 2 2")).unwrap();
+        assert_eq!(ship.orientation(), ShipOrientation::Irregular);
+        assert_eq!(ship.to_string(), "3-cell irregular ship at 1 1");
+
+        assert!(Ship::new('#', Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_Ship_transform() {
+        let mut ship = Ship::new('A', cells("1 2 2 2 3 2")).unwrap();
+        ship.hit();
+        let rotated = ship.transform(Symmetry::Rotate90);
+        assert_eq!(rotated.cells(), &cells("9 1 9 2 9 3")[..]);
+        assert_eq!(rotated.orientation(), ShipOrientation::Vertical);
+        assert_eq!((rotated.label(), rotated.hits()), ('A', 1));
+        let mirrored = ship.transform(Symmetry::ReflectHorizontally);
+        assert_eq!(mirrored.cells(), &cells("8 2 9 2 10 2")[..]);
+        assert_eq!(mirrored.orientation(), ShipOrientation::Horizontal);
+    }
+
+    #[test]
+    fn test_Ship_hit() {
+        let mut ship = Ship::new('#', cells("1 1 1 2")).unwrap();
+        ship.hit();
+        assert!(!ship.is_sunk());
+        ship.hit();
+        assert!(ship.is_sunk());
+        assert_eq!(ship.hits(), 2);
+    }
+}
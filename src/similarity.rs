@@ -0,0 +1,224 @@
+use std::fmt::Write as _;
+
+use crate::board::GameBoard;
+use crate::builtin::{RandomOpponent, Strategy};
+use crate::game::Game;
+use crate::game_log;
+use crate::player::{OutputLimits, Player};
+use crate::position::Position;
+use crate::random::Random;
+use crate::rules::{GameRules, TurnPolicy};
+use crate::stats::format_table;
+use crate::GameResult;
+
+/// Compare the orders of shots of the bots: every bot plays against the same seeded built-in
+/// opponents, and the pairs of bots which shoot in a similar order are reported
+#[derive(Debug, structopt::StructOpt)]
+pub struct SimilarityArgs {
+    /// The bots to compare (at least two)
+    #[structopt(parse(from_os_str))]
+    bots: Vec<std::path::PathBuf>,
+    /// How many games every bot plays, against the opponents with seeds from 0
+    #[structopt(long = "seeds", default_value = "5")]
+    seeds: u64,
+    /// Pairs of bots with at least this similarity (from 0 to 1) are reported as suspicious
+    #[structopt(long = "threshold", default_value = "0.8")]
+    threshold: f64,
+    /// Save the logs of the games as `<bot>-vs-builtin.<seed>.log` into this directory
+    #[structopt(long = "log-dir", parse(from_os_str))]
+    log_dir: Option<std::path::PathBuf>,
+    #[structopt(flatten)]
+    output_limits: OutputLimits,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub async fn similarity(args: SimilarityArgs) -> Result<(), String> {
+    args.rules.check()?;
+    if args.bots.len() < 2 {
+        return Err("at least two bots are required".to_owned());
+    }
+    if args.rules.turns != TurnPolicy::Classic || args.rules.mines != 0 {
+        return Err(
+            "only classic turns without mines are supported by the built-in opponents".to_owned(),
+        );
+    }
+    if let Some(ref log_dir) = args.log_dir {
+        std::fs::create_dir_all(log_dir)
+            .map_err(|err| format!("failed to create {}: {}", log_dir.display(), err))?;
+    }
+
+    // The shots of every bot in every game
+    let mut shot_orders = Vec::with_capacity(args.bots.len());
+    for bot_exe in &args.bots {
+        let mut games = Vec::new();
+        for seed in 0..args.seeds {
+            let opponent = RandomOpponent::new(&args.rules, seed)
+                .ok_or("the fleet cannot be placed on the board")?;
+            let mut log = String::new();
+            let shots = match Player::init(bot_exe, None, args.output_limits, &args.rules).await {
+                Ok((mut bot, bot_map)) => {
+                    play_against(&args.rules, &mut bot, bot_map, opponent, seed, &mut log).await
+                }
+                Err(_) => {
+                    let _ = writeln!(log, "{}", GameResult::Player2Win.as_str());
+                    Vec::new()
+                }
+            };
+            if let Some(ref log_dir) = args.log_dir {
+                let bot_name = bot_exe.file_stem().unwrap_or_default().to_string_lossy();
+                let path = log_dir.join(format!("{}-vs-builtin.{}.log", bot_name, seed));
+                std::fs::write(&path, log)
+                    .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+            }
+            games.push(shots);
+        }
+        shot_orders.push(games);
+    }
+
+    let mut pairs = Vec::new();
+    for (index, games) in shot_orders.iter().enumerate() {
+        for (other_index, other_games) in shot_orders.iter().enumerate().skip(index + 1) {
+            let similarity = games
+                .iter()
+                .zip(other_games)
+                .map(|(shots, other_shots)| sequence_similarity(shots, other_shots))
+                .sum::<f64>()
+                / args.seeds.max(1) as f64;
+            pairs.push((index, other_index, similarity));
+        }
+    }
+    pairs.sort_by(|(_, _, similarity), (_, _, other_similarity)| {
+        other_similarity
+            .partial_cmp(similarity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut rows = vec![vec![
+        "Bots".to_owned(),
+        "Similarity".to_owned(),
+        String::new(),
+    ]];
+    for &(index, other_index, similarity) in &pairs {
+        rows.push(vec![
+            format!(
+                "{} and {}",
+                args.bots[index].display(),
+                args.bots[other_index].display()
+            ),
+            format!("{:.1}%", similarity * 100.0),
+            if similarity >= args.threshold {
+                "suspicious".to_owned()
+            } else {
+                String::new()
+            },
+        ]);
+    }
+    println!(
+        "Orders of shots against {} seeded opponents:\n{}",
+        args.seeds,
+        format_table(&rows)
+    );
+    Ok(())
+}
+
+/// How similar the orders of shots are, from 0 to 1: the length of their longest common
+/// subsequence relative to the average length of the orders. An empty order (the bot failed or
+/// made no shots) is similar to nothing, so that two failing bots do not look like copies.
+pub fn sequence_similarity(shots: &[Position], other_shots: &[Position]) -> f64 {
+    if shots.is_empty() || other_shots.is_empty() {
+        return 0.0;
+    }
+    // The longest common subsequences of the prefixes of `shots` and `other_shots`, row by row
+    let mut previous_row = vec![0; other_shots.len() + 1];
+    let mut row = vec![0; other_shots.len() + 1];
+    for &shot in shots {
+        for (index, &other_shot) in other_shots.iter().enumerate() {
+            row[index + 1] = if shot == other_shot {
+                previous_row[index] + 1
+            } else {
+                std::cmp::max(row[index], previous_row[index + 1])
+            };
+        }
+        std::mem::swap(&mut row, &mut previous_row);
+    }
+    let common = previous_row[other_shots.len()];
+    2.0 * common as f64 / (shots.len() + other_shots.len()) as f64
+}
+
+/// Plays a classic game of the bot (player 1) against the built-in opponent (player 2), writing
+/// it into the `log` the same way the judge prints it, with the game seeded by the `seed` of the
+/// opponent. Returns the positions the bot shot at.
+async fn play_against(
+    rules: &GameRules,
+    bot: &mut Player,
+    bot_map: GameBoard,
+    opponent: RandomOpponent<GameBoard>,
+    seed: u64,
+    log: &mut String,
+) -> Vec<Position> {
+    let _ = writeln!(log, "{}", bot_map.to_string());
+    let _ = writeln!(log, "{}", opponent.board.to_string());
+
+    let mut strategy = opponent.strategy;
+    let mut game = Game::new(rules, [bot_map, opponent.board], Random::new(seed));
+    let mut bot_shots = Vec::new();
+    while let Some(turn) = game.turn() {
+        let shot_position = if turn.player() == 0 {
+            bot.next_shot_position().await
+        } else {
+            strategy.next_shot()
+        };
+        let shot_position = match shot_position {
+            Some(shot_position) => shot_position,
+            None => {
+                game.forfeit();
+                break;
+            }
+        };
+        let shot_report = match game_log::log_shot(log, &mut game, shot_position).outcome {
+            Ok(shot_report) => shot_report,
+            Err(_) => break,
+        };
+        if turn.player() == 0 {
+            bot_shots.push(shot_position);
+            bot.reply_shot_result(&shot_report).await;
+        }
+    }
+    if game.is_adjudicated() {
+        game_log::log_adjudication(log, &game);
+    }
+    let game_result = game.result().expect("the game is over");
+    let _ = writeln!(log, "{}", game_result.as_str());
+    bot_shots
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_sequence_similarity() {
+        let positions = |s: &str| Position::parse_list(s).unwrap();
+        let shots = positions("1 1 2 2 3 3 4 4");
+        assert_eq!(sequence_similarity(&shots, &shots), 1.0);
+        assert_eq!(sequence_similarity(&[], &[]), 0.0);
+        assert_eq!(sequence_similarity(&shots, &[]), 0.0);
+        assert_eq!(sequence_similarity(&[], &shots), 0.0);
+        assert_eq!(
+            sequence_similarity(&shots, &positions("5 5 6 6 7 7 8 8")),
+            0.0
+        );
+        // `2 2` and `4 4` are in the same order, and `1 1` and `3 3` are not
+        assert_eq!(
+            sequence_similarity(&shots, &positions("3 3 2 2 1 1 4 4")),
+            0.5
+        );
+        assert_eq!(
+            sequence_similarity(&shots, &positions("1 1 2 2 3 3 4 4 5 5 6 6 7 7 8 8")),
+            8.0 / 12.0
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use std::sync::Arc;
+
+use crate::bitboard::BitBoard;
+use crate::board::{Board, ShotReport};
+use crate::builtin::{Strategy, StrategyKind};
+use crate::game::{Game, Turn};
+use crate::placement;
+use crate::position::Position;
+use crate::random::Random;
+use crate::rules::GameRules;
+use crate::stats::format_table;
+use crate::GameResult;
+
+/// Play the built-in strategies against each other in-process, without bots, to compare them
+/// and tune their parameters
+///
+/// Every pair of the strategies plays the given number of games, with each of them moving first
+/// in half of the games. The boards are picked from a pool of random placements.
+#[derive(Debug, structopt::StructOpt)]
+pub struct SimulateArgs {
+    /// The strategies to play (at least two): `random`, `hunt` or `hunt:<parity>` to hunt for
+    /// ships on every `parity`-th diagonal only
+    strategies: Vec<StrategyKind>,
+    /// How many games every pair of the strategies plays
+    #[structopt(long = "games", default_value = "10000")]
+    games: u32,
+    /// The seed of the boards and the shots, so that the same seed (and the same number of
+    /// threads) gives the same results
+    #[structopt(long = "seed", default_value = "0")]
+    seed: u64,
+    /// How many threads play the games of every pair of the strategies
+    #[structopt(long = "threads", default_value = "1")]
+    threads: u32,
+    /// How many random boards the players pick from
+    #[structopt(long = "boards", default_value = "1000")]
+    boards: usize,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub fn simulate(args: SimulateArgs) -> Result<(), String> {
+    args.rules.check()?;
+    if args.strategies.len() < 2 {
+        return Err("at least two strategies are required".to_owned());
+    }
+    let mut random = Random::new(args.seed);
+    let boards: Arc<Vec<BitBoard>> = Arc::new(
+        (0..std::cmp::max(args.boards, 1))
+            .map(|_| placement::random_board(&args.rules, &mut random))
+            .collect::<Option<_>>()
+            .ok_or("the fleet and the mines cannot be placed on the board")?,
+    );
+    let threads = std::cmp::max(args.threads, 1);
+
+    let started_at = std::time::Instant::now();
+    let mut matchups = Vec::new();
+    for (index, &strategy) in args.strategies.iter().enumerate() {
+        for &opponent in args.strategies.iter().skip(index + 1) {
+            // Every thread plays its share of the games with its own seed
+            let handles: Vec<_> = (0..threads)
+                .map(|thread| {
+                    let games = args.games / threads + u32::from(thread < args.games % threads);
+                    let rules = args.rules.clone();
+                    let boards = Arc::clone(&boards);
+                    let mut random = Random::new(random.next_u64());
+                    std::thread::spawn(move || {
+                        play_matchup(&rules, &boards, [strategy, opponent], games, &mut random)
+                    })
+                })
+                .collect();
+            let mut matchup = Matchup::new([strategy, opponent]);
+            for handle in handles {
+                matchup.merge(&handle.join().map_err(|_| "a simulation thread panicked")?);
+            }
+            matchups.push(matchup);
+        }
+    }
+    let elapsed = started_at.elapsed();
+
+    let mut rows = vec![vec![
+        "Strategy".to_owned(),
+        "Opponent".to_owned(),
+        "Games".to_owned(),
+        "Wins".to_owned(),
+        "Draws".to_owned(),
+        "Losses".to_owned(),
+        "Shots to win".to_owned(),
+    ]];
+    for matchup in &matchups {
+        rows.push(matchup.to_row());
+    }
+    let games_count = u64::from(args.games) * matchups.len() as u64;
+    let seconds = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+    println!(
+        "{}\nSimulated {} games in {:.2} s ({:.0} games per second)",
+        format_table(&rows),
+        games_count,
+        seconds,
+        games_count as f64 / seconds.max(1e-9)
+    );
+    Ok(())
+}
+
+/// The results of the games of two strategies from the point of view of the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matchup {
+    pub strategies: [StrategyKind; 2],
+    pub games: u32,
+    pub wins: [u32; 2],
+    pub draws: u32,
+    /// The total number of shots each strategy made in the games it won.
+    pub shots_to_win: [u64; 2],
+}
+
+impl Matchup {
+    pub fn new(strategies: [StrategyKind; 2]) -> Self {
+        Self {
+            strategies,
+            games: 0,
+            wins: [0; 2],
+            draws: 0,
+            shots_to_win: [0; 2],
+        }
+    }
+
+    /// Adds a game in which the strategy with the `first_player` index moved first.
+    pub fn add(&mut self, first_player: usize, summary: &GameSummary) {
+        self.games += 1;
+        let winner = match summary.result {
+            GameResult::Draw => {
+                self.draws += 1;
+                return;
+            }
+            GameResult::Player1Win => 0,
+            GameResult::Player2Win => 1,
+        };
+        let strategy = (first_player + winner) % 2;
+        self.wins[strategy] += 1;
+        self.shots_to_win[strategy] += u64::from(summary.shots[winner]);
+    }
+
+    /// Adds the games of another matchup of the same strategies.
+    pub fn merge(&mut self, other: &Self) {
+        self.games += other.games;
+        self.draws += other.draws;
+        for strategy in 0..2 {
+            self.wins[strategy] += other.wins[strategy];
+            self.shots_to_win[strategy] += other.shots_to_win[strategy];
+        }
+    }
+
+    fn to_row(&self) -> Vec<String> {
+        let percent = |count: u32| {
+            format!(
+                "{:.1}%",
+                f64::from(count) * 100.0 / f64::from(std::cmp::max(self.games, 1))
+            )
+        };
+        let average_shots = |strategy: usize| {
+            if self.wins[strategy] == 0 {
+                "-".to_owned()
+            } else {
+                format!(
+                    "{:.1}",
+                    self.shots_to_win[strategy] as f64 / f64::from(self.wins[strategy])
+                )
+            }
+        };
+        vec![
+            self.strategies[0].to_string(),
+            self.strategies[1].to_string(),
+            self.games.to_string(),
+            percent(self.wins[0]),
+            percent(self.draws),
+            percent(self.wins[1]),
+            format!("{} / {}", average_shots(0), average_shots(1)),
+        ]
+    }
+}
+
+/// Plays `games` games of the strategies on boards picked from the `boards`, alternating which
+/// of the strategies moves first.
+pub fn play_matchup(
+    rules: &GameRules,
+    boards: &[BitBoard],
+    strategies: [StrategyKind; 2],
+    games: u32,
+    random: &mut Random,
+) -> Matchup {
+    let mut matchup = Matchup::new(strategies);
+    for game in 0..games {
+        let first_player = (game % 2) as usize;
+        let mut player1 = strategies[first_player].create(rules, Random::new(random.next_u64()));
+        let mut player2 =
+            strategies[1 - first_player].create(rules, Random::new(random.next_u64()));
+        let boards = [
+            boards[random.below(boards.len())].clone(),
+            boards[random.below(boards.len())].clone(),
+        ];
+        let game_random = Random::new(random.next_u64());
+        let summary = play_game(rules, boards, [&mut *player1, &mut *player2], game_random);
+        matchup.add(first_player, &summary);
+    }
+    matchup
+}
+
+/// How a simulated game ended.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GameSummary {
+    pub result: GameResult,
+    /// The number of shots each player made.
+    pub shots: [u16; 2],
+}
+
+/// Plays a game of the strategies on their boards (player 1's ones go first) synchronously, by
+/// the same rules the judge plays the bots by, with the `random` generator of the game.
+pub fn play_game<B: Board>(
+    rules: &GameRules,
+    boards: [B; 2],
+    mut strategies: [&mut dyn Strategy; 2],
+    random: Random,
+) -> GameSummary {
+    let mut game = Game::new(rules, boards, random);
+    while let Some(turn) = game.turn() {
+        let player = turn.player();
+        // Single shots are not collected into a volley to keep the simulation fast
+        let positions = match turn {
+            Turn::Shot { .. } => match strategies[player].next_shot() {
+                Some(position) => {
+                    if let Ok(shot_report) = game.shoot(position) {
+                        tell_shot_result(&mut strategies, player, position, &shot_report);
+                    }
+                    continue;
+                }
+                None => None,
+            },
+            Turn::Volley { size, .. } => strategies[player].next_volley(size),
+        };
+        let positions = match positions {
+            Some(positions) => positions,
+            None => {
+                game.forfeit();
+                break;
+            }
+        };
+        let mut shot_reports = Vec::with_capacity(positions.len());
+        for &position in &positions {
+            match game.shoot(position) {
+                Ok(shot_report) => shot_reports.push(shot_report),
+                Err(_) => break,
+            }
+        }
+        for (&position, shot_report) in positions.iter().zip(&shot_reports) {
+            tell_shot_result(&mut strategies, player, position, shot_report);
+        }
+    }
+    GameSummary {
+        result: game.result().expect("the game is over"),
+        shots: game.shots(),
+    }
+}
+
+/// Tells the player's strategy the result of their shot, and the opponent's one the revealed
+/// cell if the shot hit a mine.
+fn tell_shot_result(
+    strategies: &mut [&mut dyn Strategy; 2],
+    player: usize,
+    position: Position,
+    shot_report: &ShotReport,
+) {
+    strategies[player].shot_result(position, shot_report);
+    if let Some(revealed_cell) = shot_report.revealed_cell {
+        strategies[1 - player].revealed_cell(revealed_cell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::builtin::RandomStrategy;
+    use crate::fixtures::rules;
+    use crate::rules::TurnPolicy;
+
+    fn boards(rules: &GameRules, seed: u64) -> [BitBoard; 2] {
+        let mut random = Random::new(seed);
+        [
+            placement::random_board(rules, &mut random).unwrap(),
+            placement::random_board(rules, &mut random).unwrap(),
+        ]
+    }
+
+    /// Gives up after the given number of shots.
+    struct GiveUp(u16);
+
+    /// Shoots at the same cell over and over.
+    struct Repeat;
+
+    impl Strategy for Repeat {
+        fn next_shot(&mut self) -> Option<Position> {
+            Some(Position::top_left())
+        }
+    }
+
+    impl Strategy for GiveUp {
+        fn next_shot(&mut self) -> Option<Position> {
+            self.0 = self.0.checked_sub(1)?;
+            Position::top_left().iter().nth(usize::from(self.0))
+        }
+    }
+
+    #[test]
+    fn test_play_game() {
+        for &args in &[
+            &[][..],
+            &["--turns", "alternate"],
+            &["--turns", "salvo"],
+            &["--mines", "3"],
+            &["--reveal-sunk"],
+            &["--mines", "3", "--mine-penalty", "reveal"],
+            &["--placement", "any", "--repeat-shots", "forfeit"],
+        ] {
+            let rules = rules(args);
+            for seed in 0..20 {
+                let mut hunt = StrategyKind::Hunt { parity: 2 }.create(&rules, Random::new(seed));
+                let mut random = RandomStrategy::new(Random::new(seed));
+                let summary = play_game(
+                    &rules,
+                    boards(&rules, seed),
+                    [&mut *hunt, &mut random],
+                    Random::new(seed),
+                );
+                assert_ne!(summary.result, GameResult::Draw, "{:?}", args);
+                if rules.turns == TurnPolicy::Alternate {
+                    assert!(summary.shots[0] - summary.shots[1] <= 1);
+                }
+            }
+        }
+
+        let rules = rules(&[]);
+        let mut random = RandomStrategy::new(Random::new(0));
+        let summary = play_game(
+            &rules,
+            boards(&rules, 0),
+            [&mut GiveUp(0), &mut random],
+            Random::new(0),
+        );
+        assert_eq!(summary.result, GameResult::Player2Win);
+        assert_eq!(summary.shots, [0, 0]);
+
+        // The strategy loses as soon as it shoots at the same cell twice
+        let rules = self::rules(&["--repeat-shots", "forfeit"]);
+        let mut random = RandomStrategy::new(Random::new(0));
+        let summary = play_game(
+            &rules,
+            boards(&rules, 0),
+            [&mut random, &mut Repeat],
+            Random::new(0),
+        );
+        assert_eq!(summary.result, GameResult::Player1Win);
+        assert!(summary.shots[1] <= 2);
+
+        let rules = self::rules(&["--max-shots", "10", "--adjudication", "hits-left"]);
+        let mut first = RandomStrategy::new(Random::new(1));
+        let mut second = RandomStrategy::new(Random::new(2));
+        let summary = play_game(
+            &rules,
+            boards(&rules, 0),
+            [&mut first, &mut second],
+            Random::new(0),
+        );
+        assert_eq!(summary.shots, [10, 10]);
+    }
+
+    #[test]
+    fn test_play_matchup() {
+        let rules = GameRules::default();
+        let boards = boards(&rules, 0);
+        let strategies = [StrategyKind::Hunt { parity: 2 }, StrategyKind::Random];
+        let matchup = play_matchup(&rules, &boards, strategies, 100, &mut Random::new(0));
+        assert_eq!(matchup.games, 100);
+        assert_eq!(matchup.wins[0] + matchup.wins[1] + matchup.draws, 100);
+        assert!(matchup.wins[0] > 90, "{:?}", matchup);
+        assert!(matchup.shots_to_win[0] < u64::from(matchup.wins[0]) * 100);
+        assert_eq!(
+            matchup,
+            play_matchup(&rules, &boards, strategies, 100, &mut Random::new(0))
+        );
+
+        let mut matchup = Matchup::new(strategies);
+        matchup.add(
+            1,
+            &GameSummary {
+                result: GameResult::Player1Win,
+                shots: [40, 50],
+            },
+        );
+        matchup.add(
+            0,
+            &GameSummary {
+                result: GameResult::Draw,
+                shots: [200, 200],
+            },
+        );
+        assert_eq!((matchup.wins, matchup.draws), ([0, 1], 1));
+        assert_eq!(matchup.shots_to_win, [0, 40]);
+        assert_eq!(
+            matchup.to_row(),
+            ["hunt:2", "random", "2", "0.0%", "50.0%", "50.0%", "- / 40.0"]
+        );
+        let mut merged = matchup.clone();
+        merged.merge(&matchup);
+        assert_eq!((merged.games, merged.wins, merged.draws), (4, [0, 2], 2));
+        assert_eq!(merged.shots_to_win, [0, 80]);
+    }
+}
@@ -0,0 +1,525 @@
+use std::fmt::Write as _;
+
+use crate::board::GameBoard;
+use crate::cells::{GameBoardCell, GameBoardCellState};
+use crate::game_log::GameLog;
+use crate::knowledge::{KnowledgeBoard, KnowledgeCell};
+use crate::position::Position;
+use crate::raster::Raster;
+use crate::rules::GameRules;
+use crate::GAME_BOARD_SIZE;
+
+pub const CELL_SIZE: i32 = 32;
+/// Width and height of a board image: the cells with a row and a column of coordinate labels.
+pub const BOARD_IMAGE_SIZE: i32 = CELL_SIZE * (GAME_BOARD_SIZE as i32 + 1) + CELL_SIZE / 4;
+
+/// Which cells of the board are shown.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BoardView {
+    /// The whole board as its owner sees it.
+    Full,
+    /// Only what the opponent knows about the board ("fog of war").
+    Fog,
+}
+
+impl std::str::FromStr for BoardView {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "fog" => Ok(Self::Fog),
+            _ => Err("expected `full` or `fog`".to_owned()),
+        }
+    }
+}
+
+/// How a cell is drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Glyph {
+    Water,
+    Fog,
+    Ship,
+    Miss,
+    Hit,
+    Sunk,
+    Mine,
+    ExplodedMine,
+}
+
+/// Colors of the images, the order matches the `PALETTE`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Color {
+    Background,
+    Label,
+    Grid,
+    Water,
+    Fog,
+    Ship,
+    Miss,
+    Hit,
+    Sunk,
+    Mine,
+    Highlight,
+}
+
+pub const PALETTE: [[u8; 3]; 11] = [
+    [0xFF, 0xFF, 0xFF],
+    [0x44, 0x44, 0x44],
+    [0x99, 0xAA, 0xBB],
+    [0xE6, 0xF2, 0xFF],
+    [0xB8, 0xBE, 0xC6],
+    [0x6B, 0x7B, 0x8C],
+    [0x33, 0x55, 0x99],
+    [0xE5, 0x39, 0x35],
+    [0x8B, 0x1A, 0x1A],
+    [0x22, 0x22, 0x22],
+    [0xFF, 0xAA, 0x00],
+];
+
+impl Color {
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    fn hex(self) -> String {
+        let [red, green, blue] = PALETTE[usize::from(self.index())];
+        format!("#{:02x}{:02x}{:02x}", red, green, blue)
+    }
+}
+
+/// Drawing primitives shared by the vector (SVG) and the raster (PNG, GIF) images.
+pub trait Canvas {
+    fn rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color);
+    fn circle(&mut self, center: (f32, f32), radius: f32, color: Color);
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Color);
+    /// Writes a short text `size` pixels high centered at the `center`.
+    fn text(&mut self, center: (i32, i32), text: &str, size: i32, color: Color);
+}
+
+impl Canvas for Raster {
+    fn rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        self.fill_rect(x, y, width, height, color.index());
+    }
+
+    fn circle(&mut self, center: (f32, f32), radius: f32, color: Color) {
+        self.fill_circle(center, radius, color.index());
+    }
+
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Color) {
+        Raster::line(self, from, to, width, color.index());
+    }
+
+    fn text(&mut self, center: (i32, i32), text: &str, size: i32, color: Color) {
+        // The built-in font is 5 pixels high
+        Raster::text(
+            self,
+            center,
+            text,
+            std::cmp::max(1, size / 5),
+            color.index(),
+        );
+    }
+}
+
+/// An SVG image that is built element by element.
+pub struct Svg {
+    width: i32,
+    height: i32,
+    elements: String,
+}
+
+impl Svg {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            elements: String::new(),
+        }
+    }
+
+    /// A rectangle which lets what is under it show through unless the `opacity` is 1.
+    pub fn translucent_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color: Color,
+        opacity: f32,
+    ) {
+        let _ = writeln!(
+            self.elements,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"{:.3}\"/>",
+            x,
+            y,
+            width,
+            height,
+            color.hex(),
+            opacity
+        );
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+             viewBox=\"0 0 {0} {1}\">\n{2}</svg>\n",
+            self.width, self.height, self.elements
+        )
+    }
+}
+
+impl Canvas for Svg {
+    fn rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let _ = writeln!(
+            self.elements,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            x,
+            y,
+            width,
+            height,
+            color.hex()
+        );
+    }
+
+    fn circle(&mut self, center: (f32, f32), radius: f32, color: Color) {
+        let _ = writeln!(
+            self.elements,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+            center.0,
+            center.1,
+            radius,
+            color.hex()
+        );
+    }
+
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Color) {
+        let _ = writeln!(
+            self.elements,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" \
+             stroke-linecap=\"round\"/>",
+            from.0,
+            from.1,
+            to.0,
+            to.1,
+            color.hex(),
+            width
+        );
+    }
+
+    fn text(&mut self, center: (i32, i32), text: &str, size: i32, color: Color) {
+        let text = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let _ = writeln!(
+            self.elements,
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\" \
+             text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+            center.0,
+            center.1,
+            size * 4 / 3,
+            color.hex(),
+            text
+        );
+    }
+}
+
+/// Glyphs of the board cells row by row. The full view has a distinct glyph for every cell
+/// state (shot ship cells are drawn as sunk once the whole ship is sunk), and the fog view only
+/// shows the shot cells.
+pub fn glyphs(board: &GameBoard, view: BoardView) -> Vec<Glyph> {
+    match view {
+        BoardView::Full => Position::top_left()
+            .iter()
+            .map(|position| match board.get(position) {
+                GameBoardCell::Empty(GameBoardCellState::NonShot) => Glyph::Water,
+                GameBoardCell::Empty(GameBoardCellState::Shot) => Glyph::Miss,
+                GameBoardCell::Ship(GameBoardCellState::NonShot) => Glyph::Ship,
+                GameBoardCell::Ship(GameBoardCellState::Shot) => match board.ship_at(position) {
+                    Some(ship) if ship.is_sunk() => Glyph::Sunk,
+                    _ => Glyph::Hit,
+                },
+                GameBoardCell::Mine(GameBoardCellState::NonShot) => Glyph::Mine,
+                GameBoardCell::Mine(GameBoardCellState::Shot) => Glyph::ExplodedMine,
+            })
+            .collect(),
+        BoardView::Fog => {
+            let knowledge = KnowledgeBoard::from(board);
+            Position::top_left()
+                .iter()
+                .map(|position| match knowledge.get(position) {
+                    KnowledgeCell::Unknown => Glyph::Fog,
+                    KnowledgeCell::Miss => Glyph::Miss,
+                    KnowledgeCell::Hit => Glyph::Hit,
+                    KnowledgeCell::Sunk => Glyph::Sunk,
+                    KnowledgeCell::Mine => Glyph::ExplodedMine,
+                    KnowledgeCell::Revealed => Glyph::Ship,
+                })
+                .collect()
+        }
+    }
+}
+
+/// Draws the board with its top left corner at the `origin`: the `x` coordinates (1 to 10) are
+/// labelled above the columns, and the `y` coordinates are labelled to the left of the rows.
+pub fn draw_board<C: Canvas>(canvas: &mut C, origin: (i32, i32), glyphs: &[Glyph]) {
+    let (left, top) = (origin.0 + CELL_SIZE, origin.1 + CELL_SIZE);
+    let board_size = CELL_SIZE * i32::from(GAME_BOARD_SIZE);
+    for index in 0..i32::from(GAME_BOARD_SIZE) {
+        let label = (index + 1).to_string();
+        let center = left + index * CELL_SIZE + CELL_SIZE / 2;
+        canvas.text((center, origin.1 + CELL_SIZE / 2), &label, 15, Color::Label);
+        canvas.text(
+            (origin.0 + CELL_SIZE / 2, center - left + top),
+            &label,
+            15,
+            Color::Label,
+        );
+    }
+    for (position, &glyph) in Position::top_left().iter().zip(glyphs) {
+        let x = left + i32::from(position.x()) * CELL_SIZE;
+        let y = top + i32::from(position.y()) * CELL_SIZE;
+        draw_glyph(canvas, x, y, glyph);
+    }
+    for index in 0..=i32::from(GAME_BOARD_SIZE) {
+        canvas.rect(
+            left + index * CELL_SIZE,
+            top,
+            1,
+            board_size + 1,
+            Color::Grid,
+        );
+        canvas.rect(
+            left,
+            top + index * CELL_SIZE,
+            board_size + 1,
+            1,
+            Color::Grid,
+        );
+    }
+}
+
+/// Outlines the cell of the board drawn by `draw_board` at the `origin`, e.g. to mark the last
+/// shot.
+pub fn highlight_cell<C: Canvas>(canvas: &mut C, origin: (i32, i32), position: Position) {
+    let x = origin.0 + CELL_SIZE * (i32::from(position.x()) + 1);
+    let y = origin.1 + CELL_SIZE * (i32::from(position.y()) + 1);
+    canvas.rect(x, y, CELL_SIZE, 3, Color::Highlight);
+    canvas.rect(x, y + CELL_SIZE - 2, CELL_SIZE, 3, Color::Highlight);
+    canvas.rect(x, y, 3, CELL_SIZE, Color::Highlight);
+    canvas.rect(x + CELL_SIZE - 2, y, 3, CELL_SIZE, Color::Highlight);
+}
+
+fn draw_glyph<C: Canvas>(canvas: &mut C, x: i32, y: i32, glyph: Glyph) {
+    let center = (
+        x as f32 + CELL_SIZE as f32 / 2.0,
+        y as f32 + CELL_SIZE as f32 / 2.0,
+    );
+    let cross = |canvas: &mut C, color| {
+        let (near, far) = (8.0, CELL_SIZE as f32 - 8.0);
+        let (x, y) = (x as f32, y as f32);
+        canvas.line((x + near, y + near), (x + far, y + far), 4.0, color);
+        canvas.line((x + far, y + near), (x + near, y + far), 4.0, color);
+    };
+    let mine = |canvas: &mut C| {
+        let spike = CELL_SIZE as f32 / 2.0 - 6.0;
+        canvas.line(
+            (center.0 - spike, center.1),
+            (center.0 + spike, center.1),
+            2.0,
+            Color::Mine,
+        );
+        canvas.line(
+            (center.0, center.1 - spike),
+            (center.0, center.1 + spike),
+            2.0,
+            Color::Mine,
+        );
+        canvas.circle(center, CELL_SIZE as f32 / 4.0, Color::Mine);
+    };
+
+    if glyph == Glyph::Fog {
+        canvas.rect(x, y, CELL_SIZE, CELL_SIZE, Color::Fog);
+        return;
+    }
+    canvas.rect(x, y, CELL_SIZE, CELL_SIZE, Color::Water);
+    match glyph {
+        Glyph::Water | Glyph::Fog => (),
+        Glyph::Ship => canvas.rect(x + 3, y + 3, CELL_SIZE - 6, CELL_SIZE - 6, Color::Ship),
+        Glyph::Miss => canvas.circle(center, CELL_SIZE as f32 / 8.0, Color::Miss),
+        Glyph::Hit => {
+            canvas.rect(x + 3, y + 3, CELL_SIZE - 6, CELL_SIZE - 6, Color::Ship);
+            cross(canvas, Color::Hit);
+        }
+        Glyph::Sunk => {
+            canvas.rect(x + 3, y + 3, CELL_SIZE - 6, CELL_SIZE - 6, Color::Sunk);
+            cross(canvas, Color::Background);
+        }
+        Glyph::Mine => mine(canvas),
+        Glyph::ExplodedMine => {
+            mine(canvas);
+            cross(canvas, Color::Hit);
+        }
+    }
+}
+
+impl GameBoard {
+    /// Renders the board into an SVG image with the coordinate labels.
+    pub fn to_svg(&self, view: BoardView) -> String {
+        let mut svg = Svg::new(BOARD_IMAGE_SIZE, BOARD_IMAGE_SIZE);
+        svg.rect(0, 0, BOARD_IMAGE_SIZE, BOARD_IMAGE_SIZE, Color::Background);
+        draw_board(&mut svg, (0, 0), &glyphs(self, view));
+        svg.finish()
+    }
+
+    /// Renders the board into an image with the `PALETTE` colors the same way as `to_svg` does.
+    pub fn to_raster(&self, view: BoardView) -> Raster {
+        let size = BOARD_IMAGE_SIZE as u16;
+        let mut raster = Raster::new(size, size, Color::Background.index());
+        draw_board(&mut raster, (0, 0), &glyphs(self, view));
+        raster
+    }
+
+    pub fn to_png(&self, view: BoardView) -> Vec<u8> {
+        self.to_raster(view).to_png(&PALETTE)
+    }
+}
+
+/// Render a board at some point of a game log into an SVG or PNG image
+#[derive(Debug, structopt::StructOpt)]
+pub struct RenderBoardArgs {
+    #[structopt(parse(from_os_str))]
+    log: std::path::PathBuf,
+    /// Whose board to render: `1` or `2`
+    #[structopt(long = "player", default_value = "1")]
+    player: u8,
+    /// Number of shots to replay before rendering the board (all of them by default)
+    #[structopt(long = "step")]
+    step: Option<usize>,
+    /// `full` (the whole board) or `fog` (only what the opponent knows about the board)
+    #[structopt(long = "view", default_value = "full")]
+    view: BoardView,
+    /// The image to write, either `.svg` or `.png` (`<log>.player<N>.svg` by default)
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<std::path::PathBuf>,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+pub fn render_board(args: RenderBoardArgs) -> Result<(), String> {
+    if args.player != 1 && args.player != 2 {
+        return Err("the player must be either 1 or 2".to_owned());
+    }
+    let log = std::fs::read_to_string(&args.log)
+        .map_err(|err| format!("failed to read {}: {}", args.log.display(), err))?;
+    let game_log = GameLog::parse(&log, &args.rules)
+        .map_err(|err| format!("failed to parse {}: {}", args.log.display(), err))?;
+    let states = game_log.replay();
+    let step = args.step.unwrap_or(game_log.shots.len());
+    let boards = match states.get(step) {
+        Some(boards) => boards,
+        None if states.is_empty() => return Err("the log has no boards".to_owned()),
+        None => return Err(format!("the game has only {} shots", game_log.shots.len())),
+    };
+    let board = &boards[usize::from(args.player - 1)];
+
+    let output = match args.output {
+        Some(output) => output,
+        None => args
+            .log
+            .with_extension(format!("player{}.svg", args.player)),
+    };
+    let image = match output.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => board.to_svg(args.view).into_bytes(),
+        Some("png") => board.to_png(args.view),
+        _ => return Err("the output must be either an .svg or a .png file".to_owned()),
+    };
+    std::fs::write(&output, image)
+        .map_err(|err| format!("failed to write {}: {}", output.display(), err))?;
+    println!("The board is saved to {}", output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures;
+
+    /// The shared board with a miss, a hit and a sunk ship.
+    fn shot_board() -> GameBoard {
+        let mut board = fixtures::board();
+        for position in &["1 1", "1 3", "2 3", "3 3", "2 2"] {
+            board.shoot(position.parse().unwrap());
+        }
+        board
+    }
+
+    #[test]
+    fn test_glyphs() {
+        let board = shot_board();
+        let full = glyphs(&board, BoardView::Full);
+        assert_eq!(
+            &full[..5],
+            &[
+                Glyph::Hit,
+                Glyph::Ship,
+                Glyph::Ship,
+                Glyph::Ship,
+                Glyph::Water
+            ]
+        );
+        assert_eq!(full[11], Glyph::Miss);
+        assert_eq!(
+            &full[20..24],
+            &[Glyph::Sunk, Glyph::Sunk, Glyph::Sunk, Glyph::Water]
+        );
+
+        let fog = glyphs(&board, BoardView::Fog);
+        assert_eq!(
+            &fog[..5],
+            &[Glyph::Hit, Glyph::Fog, Glyph::Fog, Glyph::Fog, Glyph::Fog]
+        );
+        assert_eq!(fog[11], Glyph::Miss);
+        assert_eq!(
+            &fog[20..24],
+            &[Glyph::Sunk, Glyph::Sunk, Glyph::Sunk, Glyph::Fog]
+        );
+    }
+
+    #[test]
+    fn test_GameBoard_to_svg() {
+        let svg = shot_board().to_svg(BoardView::Full);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"360\""));
+        assert_eq!(svg.matches(">10</text>").count(), 2);
+        assert_eq!(svg.matches("<text").count(), 20);
+        // The cross of the hit cell at 1 1
+        assert!(svg.contains("<line x1=\"40\" y1=\"40\" x2=\"56\" y2=\"56\""));
+
+        let svg = shot_board().to_svg(BoardView::Fog);
+        assert_eq!(
+            svg.matches(&format!("fill=\"{}\"", Color::Ship.hex()))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_GameBoard_to_raster() {
+        let pixel = |raster: &Raster, position: &str| {
+            let position: Position = position.parse().unwrap();
+            let center = |coord: u8| (CELL_SIZE * (i32::from(coord) + 1) + CELL_SIZE / 2) as u16;
+            raster.get(center(position.x()) + 5, center(position.y()))
+        };
+        let raster = shot_board().to_raster(BoardView::Full);
+        assert_eq!(pixel(&raster, "2 1"), Color::Ship.index());
+        assert_eq!(pixel(&raster, "5 1"), Color::Water.index());
+        assert_eq!(pixel(&raster, "3 3"), Color::Sunk.index());
+        let raster = shot_board().to_raster(BoardView::Fog);
+        assert_eq!(pixel(&raster, "2 1"), Color::Fog.index());
+        assert_eq!(pixel(&raster, "2 2"), Color::Water.index());
+        assert_eq!(pixel(&raster, "3 3"), Color::Sunk.index());
+    }
+}
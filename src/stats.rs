@@ -0,0 +1,555 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::board::GameBoardShotResult;
+use crate::game_log::GameLog;
+use crate::rules::GameRules;
+use crate::GameResult;
+
+/// Game lengths (the shots of both players) are grouped by this many shots.
+const LENGTH_BUCKET: usize = 20;
+/// Longer games fall into the last bucket.
+const LENGTH_BUCKETS: usize = 10;
+
+/// Compute per-bot statistics over a directory of game logs
+///
+/// The bots are identified by the names of the log files, `<bot1>-vs-<bot2>.log` (anything after
+/// the first dot is ignored, so `alpha-vs-beta.7.log` works too). The players of other logs are
+/// counted as `player 1` and `player 2`.
+#[derive(Debug, structopt::StructOpt)]
+pub struct StatsArgs {
+    #[structopt(parse(from_os_str))]
+    dir: std::path::PathBuf,
+    /// `table` or `csv`
+    #[structopt(long = "format", default_value = "table")]
+    format: StatsFormat,
+    #[structopt(flatten)]
+    rules: GameRules,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StatsFormat {
+    Table,
+    Csv,
+}
+
+impl std::str::FromStr for StatsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("unknown format `{}`, expected `table` or `csv`", s)),
+        }
+    }
+}
+
+pub fn stats(args: StatsArgs) -> Result<(), String> {
+    let mut collector = StatsCollector::default();
+    for (names, game_log) in read_logs(&args.dir, &args.rules)? {
+        collector.add(names, &game_log);
+    }
+    match args.format {
+        StatsFormat::Table => print!("{}", collector.to_table()),
+        StatsFormat::Csv => print!("{}", collector.to_csv()),
+    }
+    Ok(())
+}
+
+/// Reads the `*.log` files of the directory in the order of their names with the names of the
+/// players (see `player_names`). The logs which fail to parse and the logs of unfinished games
+/// (without the verdict, which would be counted as lost by both players) are skipped with a
+/// warning.
+pub fn read_logs(
+    dir: &std::path::Path,
+    rules: &GameRules,
+) -> Result<Vec<([String; 2], GameLog)>, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|err| format!("failed to read {}: {}", dir.display(), err))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| format!("failed to read {}: {}", dir.display(), err))?
+            .path();
+        if path.is_file() && path.extension() == Some("log".as_ref()) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut logs = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let game_log = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|log| GameLog::parse(&log, rules));
+        match game_log {
+            Ok(ref game_log) if game_log.result.is_none() => {
+                eprintln!("Skipping {}: the game is not finished", path.display())
+            }
+            Ok(game_log) => {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                logs.push((player_names(&file_name), game_log));
+            }
+            Err(err) => eprintln!("Skipping {}: {}", path.display(), err),
+        }
+    }
+    if logs.is_empty() {
+        return Err(format!("no game logs found in {}", dir.display()));
+    }
+    Ok(logs)
+}
+
+/// The names of the first and the second player from the name of a log file.
+pub fn player_names(file_name: &str) -> [String; 2] {
+    let stem = file_name.split('.').next().unwrap_or_default();
+    let names: Vec<&str> = stem.split("-vs-").collect();
+    match names.as_slice() {
+        [player1, player2] if !player1.is_empty() && !player2.is_empty() => {
+            [(*player1).to_owned(), (*player2).to_owned()]
+        }
+        _ => ["player 1".to_owned(), "player 2".to_owned()],
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct BotStats {
+    pub name: String,
+    /// Games played as the first and as the second player.
+    pub games: [u32; 2],
+    /// Games won as the first and as the second player.
+    pub wins: [u32; 2],
+    pub draws: u32,
+    /// The shots fired in the won games.
+    pub shots_to_win: u32,
+    /// The won games with the shots in the log (not won because the opponent failed to start).
+    pub won_games_with_shots: u32,
+    /// The shots that did not violate the rules.
+    pub shots: u32,
+    /// The shots which hit or sank a ship.
+    pub hits: u32,
+    /// The shots from the first hit of a ship until it sank, including both.
+    pub shots_to_sink: u32,
+    pub sunk_ships: u32,
+    /// The number of the shots of both players in every game.
+    pub game_lengths: Vec<usize>,
+}
+
+impl BotStats {
+    pub fn games_count(&self) -> u32 {
+        self.games[0] + self.games[1]
+    }
+
+    pub fn wins_count(&self) -> u32 {
+        self.wins[0] + self.wins[1]
+    }
+
+    pub fn losses_count(&self) -> u32 {
+        self.games_count() - self.wins_count() - self.draws
+    }
+
+    pub fn win_rate(&self) -> Option<f64> {
+        ratio(self.wins_count(), self.games_count())
+    }
+
+    /// The win rate as the first (`seat` 0) or the second (`seat` 1) player.
+    pub fn seat_win_rate(&self, seat: usize) -> Option<f64> {
+        ratio(self.wins[seat], self.games[seat])
+    }
+
+    /// How much more often the bot wins when it shoots first.
+    pub fn first_move_advantage(&self) -> Option<f64> {
+        Some(self.seat_win_rate(0)? - self.seat_win_rate(1)?)
+    }
+
+    pub fn average_shots_to_win(&self) -> Option<f64> {
+        ratio(self.shots_to_win, self.won_games_with_shots)
+    }
+
+    pub fn hit_rate(&self) -> Option<f64> {
+        ratio(self.hits, self.shots)
+    }
+
+    pub fn average_shots_to_sink(&self) -> Option<f64> {
+        ratio(self.shots_to_sink, self.sunk_ships)
+    }
+
+    /// The shortest, the median and the longest game.
+    pub fn game_length_summary(&self) -> Option<(usize, usize, usize)> {
+        let mut lengths = self.game_lengths.clone();
+        lengths.sort();
+        Some((
+            *lengths.first()?,
+            lengths[(lengths.len() - 1) / 2],
+            *lengths.last()?,
+        ))
+    }
+
+    /// The number of games in every bucket of `LENGTH_BUCKET` shots.
+    pub fn game_length_distribution(&self) -> [u32; LENGTH_BUCKETS] {
+        let mut distribution = [0; LENGTH_BUCKETS];
+        for &length in &self.game_lengths {
+            distribution[std::cmp::min(length / LENGTH_BUCKET, LENGTH_BUCKETS - 1)] += 1;
+        }
+        distribution
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StatsCollector {
+    /// The bots in the order they were met.
+    pub bots: Vec<BotStats>,
+    pub games: u32,
+    /// The games won by the first and by the second player.
+    pub seat_wins: [u32; 2],
+}
+
+impl StatsCollector {
+    pub fn add(&mut self, names: [String; 2], game_log: &GameLog) {
+        self.games += 1;
+        match game_log.result {
+            Some(GameResult::Player1Win) => self.seat_wins[0] += 1,
+            Some(GameResult::Player2Win) => self.seat_wins[1] += 1,
+            _ => (),
+        }
+        for (seat, name) in names.iter().enumerate() {
+            let player_number = seat as u8 + 1;
+            let index = match self.bots.iter().position(|bot| &bot.name == name) {
+                Some(index) => index,
+                None => {
+                    self.bots.push(BotStats {
+                        name: name.clone(),
+                        ..BotStats::default()
+                    });
+                    self.bots.len() - 1
+                }
+            };
+            let bot = &mut self.bots[index];
+            bot.games[seat] += 1;
+            let has_won = match game_log.result {
+                Some(GameResult::Draw) => {
+                    bot.draws += 1;
+                    false
+                }
+                Some(GameResult::Player1Win) => player_number == 1,
+                Some(GameResult::Player2Win) => player_number == 2,
+                None => false,
+            };
+            if has_won {
+                bot.wins[seat] += 1;
+            }
+
+            let opponent_board = match game_log.boards {
+                Some(ref boards) => &boards[1 - seat],
+                None => continue,
+            };
+            bot.game_lengths.push(game_log.shots.len());
+            // The number of the player's shot which first hit a ship, by the first cell of the ship
+            let mut first_hits = HashMap::new();
+            let mut shots = 0;
+            for shot in &game_log.shots {
+                if shot.player_number != player_number {
+                    continue;
+                }
+                shots += 1;
+                let shot_report = match shot.outcome {
+                    Ok(ref shot_report) => shot_report,
+                    Err(_) => continue,
+                };
+                bot.shots += 1;
+                if shot.is_repeated {
+                    continue;
+                }
+                let result = shot_report.result;
+                if result != GameBoardShotResult::Hit && result != GameBoardShotResult::Sunk {
+                    continue;
+                }
+                bot.hits += 1;
+                let ship = match opponent_board.ship_at(shot.position) {
+                    Some(ship) => ship.cells()[0].yx(),
+                    None => continue,
+                };
+                let first_hit = *first_hits.entry(ship).or_insert(shots);
+                if result == GameBoardShotResult::Sunk {
+                    bot.shots_to_sink += shots - first_hit + 1;
+                    bot.sunk_ships += 1;
+                }
+            }
+            if has_won {
+                bot.shots_to_win += shots;
+                bot.won_games_with_shots += 1;
+            }
+        }
+    }
+
+    /// The rate of the games won by the first player minus the rate of the games won by the
+    /// second one.
+    pub fn first_move_advantage(&self) -> Option<f64> {
+        ratio(self.seat_wins[0], self.games)
+            .map(|rate| rate - f64::from(self.seat_wins[1]) / f64::from(self.games))
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut rows = vec![vec![
+            "Bot".to_owned(),
+            "Games".to_owned(),
+            "Won".to_owned(),
+            "Draw".to_owned(),
+            "Lost".to_owned(),
+            "Win %".to_owned(),
+            "as 1st".to_owned(),
+            "as 2nd".to_owned(),
+            "1st move adv.".to_owned(),
+            "Shots to win".to_owned(),
+            "Hit %".to_owned(),
+            "Shots to sink".to_owned(),
+            "Length min/med/max".to_owned(),
+        ]];
+        for bot in &self.bots {
+            let percent = |rate: Option<f64>| {
+                rate.map_or("-".to_owned(), |rate| format!("{:.1}", rate * 100.0))
+            };
+            let average =
+                |value: Option<f64>| value.map_or("-".to_owned(), |value| format!("{:.1}", value));
+            rows.push(vec![
+                bot.name.clone(),
+                bot.games_count().to_string(),
+                bot.wins_count().to_string(),
+                bot.draws.to_string(),
+                bot.losses_count().to_string(),
+                percent(bot.win_rate()),
+                percent(bot.seat_win_rate(0)),
+                percent(bot.seat_win_rate(1)),
+                percent(bot.first_move_advantage()),
+                average(bot.average_shots_to_win()),
+                percent(bot.hit_rate()),
+                average(bot.average_shots_to_sink()),
+                bot.game_length_summary()
+                    .map_or("-".to_owned(), |(min, median, max)| {
+                        format!("{}/{}/{}", min, median, max)
+                    }),
+            ]);
+        }
+        let mut table = format_table(&rows);
+
+        let mut rows = vec![Some("Game length".to_owned())
+            .into_iter()
+            .chain(self.bots.iter().map(|bot| bot.name.clone()))
+            .collect::<Vec<_>>()];
+        let distributions: Vec<_> = self
+            .bots
+            .iter()
+            .map(BotStats::game_length_distribution)
+            .collect();
+        for bucket in 0..LENGTH_BUCKETS {
+            if distributions
+                .iter()
+                .all(|distribution| distribution[bucket] == 0)
+            {
+                continue;
+            }
+            let mut row = vec![length_bucket_name(bucket, "-")];
+            row.extend(
+                distributions
+                    .iter()
+                    .map(|distribution| distribution[bucket].to_string()),
+            );
+            rows.push(row);
+        }
+        if rows.len() > 1 {
+            table.push('\n');
+            table.push_str(&format_table(&rows));
+        }
+
+        let _ = writeln!(
+            table,
+            "\n{} games: the first player won {}, the second player won {}",
+            self.games, self.seat_wins[0], self.seat_wins[1]
+        );
+        if let Some(advantage) = self.first_move_advantage() {
+            let _ = writeln!(table, "First move advantage: {:+.1}%", advantage * 100.0);
+        }
+        table
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = "bot,games,wins,draws,losses,win_rate,win_rate_as_first,win_rate_as_second,\
+                       first_move_advantage,average_shots_to_win,hit_rate,average_shots_to_sink,\
+                       min_game_length,median_game_length,max_game_length"
+            .to_owned();
+        for bucket in 0..LENGTH_BUCKETS {
+            let _ = write!(csv, ",games_of_{}_shots", length_bucket_name(bucket, "_"));
+        }
+        csv.push('\n');
+        for bot in &self.bots {
+            let number =
+                |value: Option<f64>| value.map_or(String::new(), |value| format!("{:.4}", value));
+            let _ = write!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&bot.name),
+                bot.games_count(),
+                bot.wins_count(),
+                bot.draws,
+                bot.losses_count(),
+                number(bot.win_rate()),
+                number(bot.seat_win_rate(0)),
+                number(bot.seat_win_rate(1)),
+                number(bot.first_move_advantage()),
+                number(bot.average_shots_to_win()),
+                number(bot.hit_rate()),
+                number(bot.average_shots_to_sink()),
+            );
+            match bot.game_length_summary() {
+                Some((min, median, max)) => {
+                    let _ = write!(csv, ",{},{},{}", min, median, max);
+                }
+                None => csv.push_str(",,,"),
+            }
+            for count in bot.game_length_distribution().iter() {
+                let _ = write!(csv, ",{}", count);
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn ratio(numerator: u32, denominator: u32) -> Option<f64> {
+    if denominator == 0 {
+        None
+    } else {
+        Some(f64::from(numerator) / f64::from(denominator))
+    }
+}
+
+/// E.g. `20-39` or `180+` for the last bucket.
+fn length_bucket_name(bucket: usize, separator: &str) -> String {
+    let from = bucket * LENGTH_BUCKET;
+    if bucket + 1 == LENGTH_BUCKETS {
+        format!("{}+", from)
+    } else {
+        format!("{}{}{}", from, separator, from + LENGTH_BUCKET - 1)
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Aligns the columns: the first one to the left and the others to the right.
+pub fn format_table(rows: &[Vec<String>]) -> String {
+    let mut widths = Vec::new();
+    for row in rows {
+        for (column, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            if column == widths.len() {
+                widths.push(width);
+            } else {
+                widths[column] = std::cmp::max(widths[column], width);
+            }
+        }
+    }
+    let mut table = String::new();
+    for row in rows {
+        let mut line = String::new();
+        for (column, cell) in row.iter().enumerate() {
+            if column == 0 {
+                let _ = write!(line, "{:<width$}", cell, width = widths[column]);
+            } else {
+                let _ = write!(line, "  {:>width$}", cell, width = widths[column]);
+            }
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+    use crate::fixtures;
+
+    fn collect(logs: &[(&str, &str)]) -> StatsCollector {
+        let mut collector = StatsCollector::default();
+        for (file_name, shots) in logs {
+            let log = format!("{}{}", fixtures::log_boards(), shots);
+            let game_log = GameLog::parse(&log, &GameRules::default()).unwrap();
+            collector.add(player_names(file_name), &game_log);
+        }
+        collector
+    }
+
+    #[test]
+    fn test_player_names() {
+        assert_eq!(player_names("alpha-vs-beta.log"), ["alpha", "beta"]);
+        assert_eq!(player_names("alpha-vs-beta.3.log"), ["alpha", "beta"]);
+        assert_eq!(player_names("game.log"), ["player 1", "player 2"]);
+        assert_eq!(player_names("-vs-beta.log"), ["player 1", "player 2"]);
+    }
+
+    #[test]
+    fn test_StatsCollector_add() {
+        let collector = collect(&[
+            (
+                "alpha-vs-beta.log",
+                "1 1 1 sunk\n1 3 1 hit\n1 4 1 miss\n2 1 1 hit\n2 2 1 hit\n2 3 1 miss\n\
+                 1 3 1 hit repeat\n1 3 2 repeat\nPlayer 2 won the game!\n",
+            ),
+            (
+                "beta-vs-alpha.log",
+                "1 1 3 hit\n1 1 3 miss repeat\n1 2 3 sunk\nPlayer 1 won the game!\n",
+            ),
+            ("alpha-vs-beta.1.log", "Draw!\n"),
+        ]);
+        assert_eq!(collector.games, 3);
+        assert_eq!(collector.seat_wins, [1, 1]);
+        assert_eq!(collector.first_move_advantage(), Some(0.0));
+
+        let alpha = &collector.bots[0];
+        assert_eq!(alpha.name, "alpha");
+        assert_eq!((alpha.games, alpha.wins, alpha.draws), ([2, 1], [0, 0], 1));
+        assert_eq!(alpha.losses_count(), 2);
+        assert_eq!((alpha.shots, alpha.hits), (4, 2));
+        assert_eq!((alpha.shots_to_sink, alpha.sunk_ships), (1, 1));
+        assert_eq!(alpha.average_shots_to_win(), None);
+        assert_eq!(alpha.game_length_summary(), Some((0, 3, 8)));
+
+        let beta = &collector.bots[1];
+        assert_eq!((beta.games, beta.wins), ([1, 2], [1, 1]));
+        assert_eq!(beta.first_move_advantage(), Some(0.5));
+        assert_eq!((beta.shots, beta.hits), (6, 4));
+        assert_eq!((beta.shots_to_sink, beta.sunk_ships), (3, 1));
+        assert_eq!(beta.average_shots_to_win(), Some(3.0));
+        assert_eq!(beta.game_length_distribution()[0], 3);
+    }
+
+    #[test]
+    fn test_StatsCollector_output() {
+        let collector = collect(&[("alpha-vs-beta.log", "1 1 1 sunk\nPlayer 1 won the game!\n")]);
+        let table = collector.to_table();
+        assert!(table.starts_with("Bot    Games  Won  Draw  Lost  Win %"));
+        assert!(table.contains("\nalpha      1    1     0     0  100.0   100.0       -"));
+        assert!(table.contains("First move advantage: +100.0%"));
+
+        let csv = collector.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(",games_of_0_19_shots,games_of_20_39_shots,games_of_40_59_shots,\
+                                    games_of_60_79_shots,games_of_80_99_shots,games_of_100_119_shots,\
+                                    games_of_120_139_shots,games_of_140_159_shots,games_of_160_179_shots,\
+                                    games_of_180+_shots"));
+        assert_eq!(
+            lines[1],
+            "alpha,1,1,0,0,1.0000,1.0000,,,1.0000,1.0000,1.0000,1,1,1,1,0,0,0,0,0,0,0,0,0"
+        );
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+use tokio::sync::mpsc;
+
+/// Switches the terminal to reading the keys one by one as soon as they are pressed, without
+/// echoing them, and restores the previous mode once dropped. Does nothing if the input is not a
+/// terminal, so the keys can be piped in as well.
+pub struct RawMode {
+    saved_mode: Option<String>,
+}
+
+impl RawMode {
+    pub fn enable() -> Self {
+        let saved_mode = stty(&["-g"])
+            .filter(|_| stty(&["-icanon", "-echo", "-isig", "min", "1", "time", "0"]).is_some());
+        Self { saved_mode }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(ref saved_mode) = self.saved_mode {
+            stty(&[saved_mode.trim()]);
+        }
+    }
+}
+
+/// Runs `stty` on the terminal of the judge and returns its output if it succeeds.
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    /// Ctrl+C, which does not interrupt the judge in the raw mode.
+    Interrupt,
+    Char(char),
+    /// An escape sequence of a key that has no use, e.g. F1.
+    Unknown,
+}
+
+/// Reads the keys pressed by the human on a thread of its own, so waiting for the human blocks
+/// neither the runtime nor the tasks talking to the bot.
+pub struct Keys {
+    receiver: mpsc::UnboundedReceiver<Key>,
+}
+
+impl Keys {
+    pub fn spawn() -> Self {
+        let (mut sender, receiver) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            let mut stdin = std::io::stdin();
+            while let Some(key) = parse_key(&mut stdin) {
+                if sender.try_send(key).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { receiver }
+    }
+
+    /// The next key pressed by the human. Returns `None` once the input is closed.
+    pub async fn next(&mut self) -> Option<Key> {
+        self.receiver.recv().await
+    }
+}
+
+fn parse_key<R: Read>(input: &mut R) -> Option<Key> {
+    Some(match read_byte(input)? {
+        b'\r' | b'\n' => Key::Enter,
+        0x03 => Key::Interrupt,
+        // Arrows are sent as `ESC [ A` to `ESC [ D` (or with `O` instead of `[`), and the other
+        // sequences end with a byte from `@` to `~`, e.g. `ESC [ 1 1 ~` for F1
+        0x1b => match read_byte(input)? {
+            b'[' | b'O' => loop {
+                match read_byte(input)? {
+                    b'A' => break Key::Up,
+                    b'B' => break Key::Down,
+                    b'C' => break Key::Right,
+                    b'D' => break Key::Left,
+                    b'@'..=b'~' => break Key::Unknown,
+                    _ => (),
+                }
+            },
+            _ => Key::Unknown,
+        },
+        byte => Key::Char(char::from(byte)),
+    })
+}
+
+fn read_byte<R: Read>(input: &mut R) -> Option<u8> {
+    let mut byte = [0];
+    match input.read(&mut byte) {
+        Ok(1) => Some(byte[0]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        let mut input: &[u8] = b"w\x1b[A\x1b[D\x1bOB\r\n \x03\x1b[11~q";
+        let keys: Vec<Key> = std::iter::from_fn(|| parse_key(&mut input)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                Key::Char('w'),
+                Key::Up,
+                Key::Left,
+                Key::Down,
+                Key::Enter,
+                Key::Enter,
+                Key::Char(' '),
+                Key::Interrupt,
+                Key::Unknown,
+                Key::Char('q'),
+            ]
+        );
+    }
+}